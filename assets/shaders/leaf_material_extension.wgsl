@@ -1,4 +1,5 @@
 #import bevy_pbr::{
+    mesh_functions,
     pbr_fragment::pbr_input_from_standard_material,
     pbr_functions::alpha_discard,
 }
@@ -15,6 +16,17 @@
 }
 #endif
 
+struct Leaves {
+    wind_direction: vec2<f32>,
+    wind_strength: f32,
+    gust_phase: f32,
+    flutter_phase: f32,
+    min_leaf_size: f32,
+    max_leaf_size: f32,
+    tint_variation: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> leaves: Leaves;
 
 @fragment
 fn fragment(
@@ -32,6 +44,15 @@ fn fragment(
     // alpha discard
     pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
 
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    // Every tree gets its own shade, from darker to lighter and yellower
+    let origin = mesh_functions::get_world_from_local(in.instance_index)[3].xyz;
+    let shade = fract(sin(dot(origin, vec3<f32>(12.9898, 78.233, 37.719))) * 43758.5453123) * 2.0 - 1.0;
+    let tint = mix(vec3<f32>(1.0), vec3<f32>(1.25, 1.2, 0.7), max(shade, 0.0)) * (1.0 + min(shade, 0.0) * 0.5);
+    let varied = mix(vec3<f32>(1.0), tint, leaves.tint_variation);
+    pbr_input.material.base_color = vec4<f32>(pbr_input.material.base_color.rgb * varied, pbr_input.material.base_color.a);
+#endif
+
 #ifdef PREPASS_PIPELINE
     // in deferred mode we can't modify anything after that, as lighting is run in a separate fullscreen shader.
     let out = deferred_output(in, pbr_input);
@@ -8,6 +8,20 @@
     view_transformations::{position_world_to_clip, position_world_to_view, position_view_to_clip},
 }
 
+const TAU: f32 = 6.28318530718;
+
+struct Leaves {
+    wind_direction: vec2<f32>,
+    wind_strength: f32,
+    gust_phase: f32,
+    flutter_phase: f32,
+    min_leaf_size: f32,
+    max_leaf_size: f32,
+    tint_variation: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> leaves: Leaves;
+
 @vertex
 fn vertex(vertex: Vertex) -> VertexOutput {
     var out: VertexOutput;
@@ -25,16 +39,28 @@ fn vertex(vertex: Vertex) -> VertexOutput {
         vec4<f32>(vertex.position, 1.0)
     );
 
-    let billboard_size = fract(sin(vertex.position.x + vertex.position.y + vertex.position.z) * 43758.5453123) * 0.7;
+    let hash = fract(sin(vertex.position.x + vertex.position.y + vertex.position.z) * 43758.5453123);
+    let billboard_size = mix(leaves.min_leaf_size, leaves.max_leaf_size, hash);
     let uv_scaled = (1.0 - vertex.uv) * 2.0 - 1.0;
     let offset = vec4<f32>(-uv_scaled.x, uv_scaled.y, 0.0, 0.0) * billboard_size;
 
-    out.world_position = view.world_from_view * offset  + base_world_position;
+    // Gusts roll along the wind direction, so neighbouring trees sway together
+    let phase = dot(base_world_position.xz, leaves.wind_direction) * 0.05 - leaves.gust_phase;
+    let gust = sin(phase) * 0.5 + 0.5;
+    // Each leaf flutters a little on its own
+    let flutter = sin(leaves.flutter_phase + hash * TAU) * 0.1;
+    let sway = vec3<f32>(leaves.wind_direction.x, 0.0, leaves.wind_direction.y) * leaves.wind_strength * (gust + flutter);
+
+    out.world_position = view.world_from_view * offset  + base_world_position + vec4<f32>(sway, 0.0);
 
     out.position = position_world_to_clip(out.world_position.xyz);
 
     out.uv = vertex.uv;
 
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
     return out;
 }
 
@@ -0,0 +1,34 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+struct Sky {
+    zenith_color: vec4<f32>,
+    horizon_color: vec4<f32>,
+    sun_color: vec4<f32>,
+    sun_direction: vec3<f32>,
+    sun_size: f32,
+    sun_brightness: f32,
+    sun_glow: f32,
+    horizon_falloff: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> sky: Sky;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let direction = normalize(in.world_position.xyz - view.world_position);
+
+    // The horizon color continues below the horizon, like the fog on the ground
+    let height = 1.0 - pow(1.0 - max(direction.y, 0.0), sky.horizon_falloff);
+    var color = mix(sky.horizon_color.rgb, sky.zenith_color.rgb, height);
+
+    let sun_dot = dot(direction, normalize(sky.sun_direction));
+    let sun_angle = acos(clamp(sun_dot, -1.0, 1.0));
+    let disc = 1.0 - smoothstep(sky.sun_size * 0.8, sky.sun_size, sun_angle);
+    let glow = sky.sun_glow * pow(max(sun_dot, 0.0), 16.0);
+    color += sky.sun_color.rgb * (disc * sky.sun_brightness + glow);
+
+    return vec4(color, 1.0);
+}
@@ -0,0 +1,366 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::{
+    asset::LoadState,
+    audio::{AudioSinkPlayback, PlaybackSettings, Volume},
+    prelude::*,
+    ui_widgets::Activate,
+};
+
+use crate::{
+    enemy::Enemy,
+    game_resources::ResourceKind,
+    harvestable::Harvestable,
+    health::{DamageEvent, Dead},
+    player::PlayerHit,
+    settings::Settings,
+};
+
+/// Tracks played in turn while nothing is attacking.
+const CALM_TRACKS: &[&str] = &["audio/music/calm_1.wav"];
+
+/// Tracks played in turn while a wave is attacking.
+const COMBAT_TRACKS: &[&str] = &["audio/music/combat_1.wav"];
+
+/// Time for one track to fade out while the next one fades in.
+const CROSSFADE: Duration = Duration::from_secs(3);
+
+/// Plays sound effects requested with [`PlaySound`] and the music chosen by the
+/// [`MusicDirector`]. Named apart from Bevy's `AudioPlugin`, which does the actual playback.
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundAssets>()
+            .init_resource::<MusicDirector>()
+            .add_observer(play_sound)
+            .add_observer(hit_sound)
+            .add_observer(enemy_hit_sound)
+            .add_systems(Update, direct_music);
+    }
+}
+
+/// Volume category of a sound, each with its own volume in the [`Settings`]. The master volume
+/// applies to all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioBus {
+    Music,
+    Sfx,
+    Ui,
+}
+
+impl AudioBus {
+    pub fn volume(self, settings: &Settings) -> f32 {
+        match self {
+            AudioBus::Music => settings.music_volume,
+            AudioBus::Sfx => settings.sfx_volume,
+            AudioBus::Ui => settings.ui_volume,
+        }
+    }
+}
+
+/// A one-shot sound effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Chop,
+    Mine,
+    EnemyHit,
+    Click,
+}
+
+impl Sound {
+    pub const ALL: [Sound; 4] = [Sound::Chop, Sound::Mine, Sound::EnemyHit, Sound::Click];
+
+    pub fn path(self) -> &'static str {
+        match self {
+            Sound::Chop => "audio/sfx/chop.wav",
+            Sound::Mine => "audio/sfx/mine.wav",
+            Sound::EnemyHit => "audio/sfx/enemy_hit.wav",
+            Sound::Click => "audio/sfx/click.wav",
+        }
+    }
+
+    pub fn bus(self) -> AudioBus {
+        match self {
+            Sound::Click => AudioBus::Ui,
+            Sound::Chop | Sound::Mine | Sound::EnemyHit => AudioBus::Sfx,
+        }
+    }
+}
+
+/// Requests a sound effect, heard from `position` or everywhere alike if there is none.
+/// Observe it to check which sounds are requested, e.g. in tests without an audio device.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct PlaySound {
+    pub sound: Sound,
+    pub position: Option<Vec3>,
+}
+
+impl PlaySound {
+    pub fn at(sound: Sound, position: Vec3) -> Self {
+        Self {
+            sound,
+            position: Some(position),
+        }
+    }
+
+    pub fn global(sound: Sound) -> Self {
+        Self {
+            sound,
+            position: None,
+        }
+    }
+}
+
+/// Plays a click, observed by every [`button`](crate::ui::widgets::button).
+pub fn click_sound(_activate: On<Activate>, mut commands: Commands) {
+    commands.trigger(PlaySound::global(Sound::Click));
+}
+
+/// Situation the music is chosen for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MusicMood {
+    Calm,
+    Combat,
+}
+
+impl MusicMood {
+    /// Combat while enemies are alive, e.g. during a wave.
+    pub fn current(enemies_alive: bool) -> Self {
+        if enemies_alive {
+            MusicMood::Combat
+        } else {
+            MusicMood::Calm
+        }
+    }
+
+    pub fn playlist(self) -> &'static [&'static str] {
+        match self {
+            MusicMood::Calm => CALM_TRACKS,
+            MusicMood::Combat => COMBAT_TRACKS,
+        }
+    }
+}
+
+/// Crossfades to the next track of the current [`MusicMood`]'s playlist whenever the mood
+/// changes or a track ends.
+#[derive(Resource, Debug, Default)]
+pub struct MusicDirector {
+    /// Index of the next track in each playlist.
+    next_track: HashMap<MusicMood, usize>,
+}
+
+impl MusicDirector {
+    fn next_track(&mut self, mood: MusicMood) -> &'static str {
+        let playlist = mood.playlist();
+        let index = self.next_track.entry(mood).or_default();
+        let track = playlist[*index % playlist.len()];
+        *index = (*index + 1) % playlist.len();
+        track
+    }
+}
+
+/// A playing music track, despawned once it faded out or ended.
+#[derive(Component, Debug)]
+struct MusicTrack {
+    mood: MusicMood,
+    /// Fade level from 0 to 1, multiplied with the music volume.
+    level: f32,
+    fading_out: bool,
+}
+
+#[derive(Resource)]
+struct SoundAssets(HashMap<Sound, Handle<AudioSource>>);
+
+impl FromWorld for SoundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self(
+            Sound::ALL
+                .into_iter()
+                .map(|sound| (sound, asset_server.load(sound.path())))
+                .collect(),
+        )
+    }
+}
+
+fn play_sound(
+    play: On<PlaySound>,
+    mut commands: Commands,
+    assets: Res<SoundAssets>,
+    settings: Res<Settings>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(source) = assets.0.get(&play.sound) else {
+        return;
+    };
+    // A player whose source never loads would never finish and despawn
+    if is_failed(&asset_server, source) {
+        return;
+    }
+    let volume = play.sound.bus().volume(&settings);
+    commands.spawn((
+        Name::new(format!("Sound {:?}", play.sound)),
+        AudioPlayer(source.clone()),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(volume))
+            .with_spatial(play.position.is_some()),
+        Transform::from_translation(play.position.unwrap_or_default()),
+    ));
+}
+
+fn hit_sound(
+    hit: On<PlayerHit>,
+    mut commands: Commands,
+    harvestables: Query<(&Harvestable, &GlobalTransform)>,
+) {
+    // The hit propagates up from the collider to the node
+    let Ok((harvestable, transform)) = harvestables.get(hit.event_target()) else {
+        return;
+    };
+    let sound = match harvestable.kind {
+        ResourceKind::Stone => Sound::Mine,
+        _ => Sound::Chop,
+    };
+    commands.trigger(PlaySound::at(sound, transform.translation()));
+}
+
+fn enemy_hit_sound(
+    damage: On<DamageEvent>,
+    mut commands: Commands,
+    enemies: Query<&GlobalTransform, (With<Enemy>, Without<Dead>)>,
+) {
+    let Ok(transform) = enemies.get(damage.event_target()) else {
+        return;
+    };
+    commands.trigger(PlaySound::at(Sound::EnemyHit, transform.translation()));
+}
+
+fn direct_music(
+    mut commands: Commands,
+    mut director: ResMut<MusicDirector>,
+    mut tracks: Query<(
+        Entity,
+        &mut MusicTrack,
+        &AudioPlayer,
+        Option<&mut AudioSink>,
+    )>,
+    enemies: Query<(), (With<Enemy>, Without<Dead>)>,
+    asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
+    // Keeps fading while the game is paused
+    time: Res<Time<Real>>,
+) {
+    let mood = MusicMood::current(!enemies.is_empty());
+    let mut playing = false;
+    let step = time.delta_secs() / CROSSFADE.as_secs_f32();
+    for (entity, mut track, player, sink) in tracks.iter_mut() {
+        if is_failed(&asset_server, &player.0) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        track.fading_out |= track.mood != mood;
+        playing |= !track.fading_out;
+
+        track.level = if track.fading_out {
+            (track.level - step).max(0.0)
+        } else {
+            (track.level + step).min(1.0)
+        };
+        if track.fading_out && track.level == 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(
+                track.level * AudioBus::Music.volume(&settings),
+            ));
+        }
+    }
+
+    if !playing {
+        let track = director.next_track(mood);
+        let source = asset_server.load(track);
+        if is_failed(&asset_server, &source) {
+            return;
+        }
+        commands.spawn((
+            Name::new(format!("Music {track}")),
+            MusicTrack {
+                mood,
+                level: 0.0,
+                fading_out: false,
+            },
+            AudioPlayer::new(source),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(0.0)),
+        ));
+    }
+}
+
+fn is_failed(asset_server: &AssetServer, source: &Handle<AudioSource>) -> bool {
+    matches!(asset_server.load_state(source), LoadState::Failed(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use avian3d::prelude::RayHitData;
+
+    use super::*;
+    use crate::testing::headless_app;
+
+    /// Every [`PlaySound`] that was triggered, in order.
+    #[derive(Resource, Default)]
+    struct Requested(Vec<PlaySound>);
+
+    fn app() -> App {
+        let mut app = headless_app();
+        app.init_resource::<Settings>()
+            .init_resource::<Requested>()
+            .add_plugins(GameAudioPlugin)
+            .add_observer(|play: On<PlaySound>, mut requested: ResMut<Requested>| {
+                requested.0.push(*play);
+            });
+        app
+    }
+
+    #[test]
+    fn hitting_a_node_requests_its_sound_at_the_node() {
+        let mut app = app();
+        let position = Vec3::new(3.0, 0.0, -2.0);
+        let rock = app
+            .world_mut()
+            .spawn((
+                Harvestable::new(ResourceKind::Stone, 1),
+                GlobalTransform::from_translation(position),
+            ))
+            .id();
+        app.world_mut().trigger(PlayerHit {
+            entity: rock,
+            hit: RayHitData {
+                entity: rock,
+                distance: 1.0,
+                normal: Vec3::Y,
+            },
+            point: position,
+        });
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<Requested>().0,
+            [PlaySound::at(Sound::Mine, position)]
+        );
+    }
+
+    #[test]
+    fn activating_a_button_requests_a_click() {
+        let mut app = app();
+        let button = app.world_mut().spawn_empty().observe(click_sound).id();
+        app.world_mut().trigger(Activate { entity: button });
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<Requested>().0,
+            [PlaySound::global(Sound::Click)]
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::{collections::HashMap, fmt::Display};
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    game_resources::{Cost, ResourceKind},
+    tower::{TargetingPolicy, Tower},
+};
+
+/// Path of the catalog below the assets folder.
+pub const CATALOG_PATH: &str = "buildings.ron";
+
+pub struct CatalogPlugin;
+
+impl Plugin for CatalogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BuildingCatalog>()
+            .init_asset_loader::<BuildingCatalogLoader>()
+            .add_systems(Startup, load_catalog);
+    }
+}
+
+/// Every building the player can place, see `assets/buildings.ron`.
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct BuildingCatalog {
+    buildings: Vec<BuildingDefinition>,
+}
+
+impl BuildingCatalog {
+    pub fn new(buildings: Vec<BuildingDefinition>) -> Self {
+        Self { buildings }
+    }
+
+    pub fn buildings(&self) -> &[BuildingDefinition] {
+        &self.buildings
+    }
+
+    pub fn get(&self, id: &str) -> Option<&BuildingDefinition> {
+        self.buildings.iter().find(|building| building.id == id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildingDefinition {
+    pub id: String,
+    pub name: String,
+    pub cost: Cost,
+    pub hp: u32,
+    /// Extent of the building, its footprint is `x` by `z`.
+    pub size: Vec3,
+    /// Scene shown instead of a plain box, e.g. `buildings/house.gltf#Scene0`.
+    pub model: Option<String>,
+    pub color: Color,
+    /// Research required before the building can be placed.
+    pub requires: Vec<String>,
+    /// Makes the building shoot at enemies.
+    pub tower: Option<Tower>,
+}
+
+#[derive(Resource)]
+pub struct BuildingCatalogHandle(pub Handle<BuildingCatalog>);
+
+/// Read access to the loaded [`BuildingCatalog`].
+#[derive(SystemParam)]
+pub struct Catalog<'w> {
+    handle: Res<'w, BuildingCatalogHandle>,
+    catalogs: Res<'w, Assets<BuildingCatalog>>,
+}
+
+impl Catalog<'_> {
+    /// `None` until the catalog finished loading.
+    pub fn get(&self) -> Option<&BuildingCatalog> {
+        self.catalogs.get(&self.handle.0)
+    }
+}
+
+fn load_catalog(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BuildingCatalogHandle(asset_server.load(CATALOG_PATH)));
+}
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    buildings: Vec<BuildingEntry>,
+}
+
+#[derive(Deserialize)]
+struct BuildingEntry {
+    id: String,
+    name: String,
+    #[serde(default)]
+    cost: HashMap<String, u32>,
+    hp: u32,
+    footprint: (f32, f32),
+    height: f32,
+    #[serde(default)]
+    model: Option<String>,
+    color: (f32, f32, f32),
+    #[serde(default)]
+    requires: Vec<String>,
+    #[serde(default)]
+    tower: Option<TowerEntry>,
+}
+
+#[derive(Deserialize)]
+struct TowerEntry {
+    range: f32,
+    /// Shots per second.
+    fire_rate: f32,
+    damage: f32,
+    #[serde(default)]
+    targeting: TargetingPolicy,
+}
+
+#[derive(Debug)]
+pub enum BuildingCatalogError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    DuplicateId(String),
+    UnknownResourceKind { building: String, kind: String },
+    MissingModel { building: String, path: String },
+    InvalidSize { building: String },
+    InvalidTower { building: String },
+}
+
+impl Display for BuildingCatalogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildingCatalogError::Io(error) => write!(f, "Could not read the catalog: {error}"),
+            BuildingCatalogError::Ron(error) => write!(f, "Invalid catalog: {error}"),
+            BuildingCatalogError::DuplicateId(id) => {
+                write!(f, "Building `{id}` is defined more than once")
+            }
+            BuildingCatalogError::UnknownResourceKind { building, kind } => write!(
+                f,
+                "Building `{building}` costs unknown resource `{kind}`, expected one of {}",
+                ResourceKind::ALL.map(|kind| kind.to_string()).join(", ")
+            ),
+            BuildingCatalogError::MissingModel { building, path } => {
+                write!(f, "Model `{path}` of building `{building}` does not exist")
+            }
+            BuildingCatalogError::InvalidSize { building } => {
+                write!(
+                    f,
+                    "Building `{building}` needs a positive footprint and height"
+                )
+            }
+            BuildingCatalogError::InvalidTower { building } => {
+                write!(f, "Tower `{building}` needs a positive range and fire rate")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildingCatalogError {}
+
+#[derive(Default)]
+pub struct BuildingCatalogLoader;
+
+impl AssetLoader for BuildingCatalogLoader {
+    type Asset = BuildingCatalog;
+    type Settings = ();
+    type Error = BuildingCatalogError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(BuildingCatalogError::Io)?;
+        let file: CatalogFile = ron::de::from_bytes(&bytes).map_err(BuildingCatalogError::Ron)?;
+
+        let mut buildings: Vec<BuildingDefinition> = Vec::with_capacity(file.buildings.len());
+        for entry in file.buildings {
+            if buildings.iter().any(|building| building.id == entry.id) {
+                return Err(BuildingCatalogError::DuplicateId(entry.id));
+            }
+            if entry.footprint.0 <= 0.0 || entry.footprint.1 <= 0.0 || entry.height <= 0.0 {
+                return Err(BuildingCatalogError::InvalidSize { building: entry.id });
+            }
+
+            if entry
+                .tower
+                .as_ref()
+                .is_some_and(|tower| tower.range <= 0.0 || tower.fire_rate <= 0.0)
+            {
+                return Err(BuildingCatalogError::InvalidTower { building: entry.id });
+            }
+
+            let cost = match Cost::from_names(entry.cost) {
+                Ok(cost) => cost,
+                Err(kind) => {
+                    return Err(BuildingCatalogError::UnknownResourceKind {
+                        building: entry.id,
+                        kind,
+                    });
+                }
+            };
+
+            if let Some(model) = &entry.model {
+                // Also registers the model as a dependency, so changing it reloads the catalog
+                let model_path = AssetPath::parse(model).without_label().into_owned();
+                if load_context.read_asset_bytes(model_path).await.is_err() {
+                    return Err(BuildingCatalogError::MissingModel {
+                        building: entry.id,
+                        path: model.clone(),
+                    });
+                }
+            }
+
+            buildings.push(BuildingDefinition {
+                id: entry.id,
+                name: entry.name,
+                cost,
+                hp: entry.hp,
+                size: Vec3::new(entry.footprint.0, entry.height, entry.footprint.1),
+                model: entry.model,
+                color: Color::srgb(entry.color.0, entry.color.1, entry.color.2),
+                requires: entry.requires,
+                tower: entry.tower.map(|tower| {
+                    Tower::new(tower.range, tower.fire_rate, tower.damage, tower.targeting)
+                        .with_muzzle_height(entry.height)
+                }),
+            });
+        }
+        Ok(BuildingCatalog { buildings })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use avian3d::prelude::{Collider, RigidBody};
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    building::{
+        catalog::{BuildingCatalog, BuildingDefinition, CatalogPlugin},
+        placement::PlacementPlugin,
+    },
+    health::Health,
+};
+
+pub mod catalog;
+pub mod placement;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildingAssets>()
+            .add_plugins((CatalogPlugin, PlacementPlugin))
+            .add_systems(Update, update_building_assets);
+    }
+}
+
+/// A placed building, `id` refers to its [`BuildingDefinition`].
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct Building {
+    pub id: String,
+}
+
+/// Render assets of every building in the [`BuildingCatalog`], by id.
+#[derive(Resource, Default)]
+pub(crate) struct BuildingAssets {
+    meshes: HashMap<String, Handle<Mesh>>,
+    materials: HashMap<String, Handle<StandardMaterial>>,
+    models: HashMap<String, Handle<Scene>>,
+}
+
+impl BuildingAssets {
+    /// Whether the building with `id` can be spawned. Its assets are created once the catalog
+    /// is loaded.
+    pub(crate) fn contains(&self, id: &str) -> bool {
+        self.meshes.contains_key(id) && self.materials.contains_key(id)
+    }
+}
+
+/// Recreates the building assets whenever the catalog is (re)loaded.
+pub(crate) fn update_building_assets(
+    mut asset_events: MessageReader<AssetEvent<BuildingCatalog>>,
+    mut building_assets: ResMut<BuildingAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    catalogs: Res<Assets<BuildingCatalog>>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        let Some(catalog) = catalogs.get(*id) else {
+            continue;
+        };
+
+        *building_assets = BuildingAssets::default();
+        for building in catalog.buildings() {
+            building_assets.meshes.insert(
+                building.id.clone(),
+                meshes.add(Cuboid::from_size(building.size)),
+            );
+            building_assets.materials.insert(
+                building.id.clone(),
+                materials.add(StandardMaterial {
+                    base_color: building.color,
+                    perceptual_roughness: 1.0,
+                    ..default()
+                }),
+            );
+            if let Some(model) = &building.model {
+                building_assets
+                    .models
+                    .insert(building.id.clone(), asset_server.load(model.clone()));
+            }
+        }
+    }
+}
+
+/// Spawns the building standing on the ground at `transform`. Returns `None` if its assets
+/// don't exist.
+pub(crate) fn spawn_building(
+    commands: &mut Commands,
+    assets: &BuildingAssets,
+    building: &BuildingDefinition,
+    transform: Transform,
+) -> Option<Entity> {
+    let (Some(mesh), Some(material)) = (
+        assets.meshes.get(&building.id),
+        assets.materials.get(&building.id),
+    ) else {
+        warn!("Building `{}` has no assets, can't spawn it", building.id);
+        return None;
+    };
+    let mut entity = commands.spawn((
+        Name::new(building.name.clone()),
+        Building {
+            id: building.id.clone(),
+        },
+        Health::new(building.hp as f32),
+        transform,
+        Visibility::default(),
+        RigidBody::Static,
+        DespawnOnExit(GameState::InGame),
+        children![(
+            Collider::cuboid(building.size.x, building.size.y, building.size.z),
+            Transform::from_xyz(0.0, building.size.y / 2.0, 0.0),
+        )],
+    ));
+    if let Some(tower) = &building.tower {
+        entity.insert(tower.clone());
+    }
+    match assets.models.get(&building.id) {
+        Some(model) => {
+            entity.with_child(SceneRoot(model.clone()));
+        }
+        None => {
+            entity.with_child((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_xyz(0.0, building.size.y / 2.0, 0.0),
+            ));
+        }
+    }
+    Some(entity.id())
+}
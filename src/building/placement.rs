@@ -0,0 +1,297 @@
+use std::f32::consts::FRAC_PI_2;
+
+use avian3d::prelude::{Collider, RayCaster, RayHits, SpatialQuery, SpatialQueryFilter};
+use bevy::{light::NotShadowCaster, prelude::*};
+use puppeteer::puppet_rig::PuppetRig;
+
+use crate::{
+    GameState, PauseState,
+    building::{BuildingAssets, catalog::Catalog, spawn_building},
+    game_resources::{GameResources, Treasury},
+    input::{Action, BUILDING_SLOTS, InputActions},
+    player::PlayerInteraction,
+    research::UnlockedFeatures,
+    world::{Heightmap, Terrain},
+};
+
+/// Buildings snap to a grid of this size.
+pub const GRID_SIZE: f32 = 1.0;
+
+/// Colliders this close to the ground don't block placement.
+const GROUND_TOLERANCE: f32 = 0.05;
+
+/// Buildings can't be placed where the ground below them is more uneven than this.
+const MAX_GROUND_STEP: f32 = 1.0;
+
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>()
+            .init_resource::<GhostMaterials>()
+            .add_observer(place_building)
+            .add_systems(OnExit(GameState::InGame), reset_build_mode)
+            .add_systems(
+                Update,
+                (
+                    build_mode_input.run_if(in_state(PauseState::Running)),
+                    update_ghost,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct BuildMode {
+    pub active: bool,
+    /// Index into the buildings of the [`BuildingCatalog`](super::catalog::BuildingCatalog).
+    pub selected: usize,
+    /// Rotation around the Y axis in quarter turns.
+    pub quarter_turns: u8,
+}
+
+/// Translucent preview of the selected building at the targeted position.
+#[derive(Component, Debug, Default)]
+pub struct Ghost {
+    /// Whether the building fits at the ghost's position on level enough ground, is researched
+    /// and can be afforded.
+    pub valid: bool,
+}
+
+#[derive(Resource)]
+struct GhostMaterials {
+    valid: Handle<StandardMaterial>,
+    invalid: Handle<StandardMaterial>,
+}
+
+impl FromWorld for GhostMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut ghost_material = |color: Color| {
+            materials.add(StandardMaterial {
+                base_color: color,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        };
+        Self {
+            valid: ghost_material(Color::srgba(0.8, 0.9, 1.0, 0.4)),
+            invalid: ghost_material(Color::srgba(1.0, 0.15, 0.1, 0.4)),
+        }
+    }
+}
+
+fn reset_build_mode(mut build_mode: ResMut<BuildMode>) {
+    *build_mode = BuildMode::default();
+}
+
+fn build_mode_input(
+    mut commands: Commands,
+    mut build_mode: ResMut<BuildMode>,
+    mut ghost: Query<(Entity, &mut Mesh3d), With<Ghost>>,
+    actions: Res<InputActions>,
+    catalog: Catalog,
+    assets: Res<BuildingAssets>,
+    ghost_materials: Res<GhostMaterials>,
+) {
+    let Some(catalog) = catalog.get() else {
+        return;
+    };
+    let buildings = catalog.buildings();
+    if actions.just_pressed(Action::OpenBuildMenu) {
+        build_mode.active = !build_mode.active;
+    }
+    if let Some(selected) = (0..BUILDING_SLOTS)
+        .take(buildings.len())
+        .position(|slot| actions.just_pressed(Action::SelectBuilding(slot)))
+    {
+        build_mode.selected = selected;
+        build_mode.active = true;
+    }
+    if actions.just_pressed(Action::RotateBuilding) {
+        build_mode.quarter_turns = (build_mode.quarter_turns + 1) % 4;
+    }
+
+    // The catalog may have shrunk after a reload
+    if build_mode.selected >= buildings.len() {
+        build_mode.selected = 0;
+    }
+
+    if !build_mode.is_changed() && !assets.is_changed() {
+        return;
+    }
+    // Only show a ghost while building
+    let mesh = buildings
+        .get(build_mode.selected)
+        .and_then(|building| assets.meshes.get(&building.id))
+        .filter(|_| build_mode.active);
+    match (mesh, ghost.single_mut()) {
+        (Some(mesh), Ok((_, mut ghost_mesh))) => {
+            ghost_mesh.0 = mesh.clone();
+        }
+        (Some(mesh), Err(_)) => {
+            commands.spawn((
+                Name::new("Building Ghost"),
+                Ghost::default(),
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(ghost_materials.invalid.clone()),
+                Transform::default(),
+                Visibility::Hidden,
+                NotShadowCaster,
+                DespawnOnExit(GameState::InGame),
+            ));
+        }
+        (None, Ok((entity, _))) => {
+            commands.entity(entity).despawn();
+        }
+        (None, Err(_)) => {}
+    }
+}
+
+/// Moves the ghost to the targeted point and checks whether the building fits there.
+fn update_ghost(
+    mut ghost: Query<(
+        &mut Ghost,
+        &mut Transform,
+        &mut Visibility,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+    rays: Query<(&RayCaster, &RayHits), With<PuppetRig>>,
+    build_mode: Res<BuildMode>,
+    catalog: Catalog,
+    spatial_query: SpatialQuery,
+    game_resources: Res<GameResources>,
+    unlocked: UnlockedFeatures,
+    ghost_materials: Res<GhostMaterials>,
+    heightmap: Option<Res<Heightmap>>,
+    terrain: Query<Entity, With<Terrain>>,
+) {
+    let Ok((mut ghost, mut transform, mut visibility, mut material)) = ghost.single_mut() else {
+        return;
+    };
+    let Some((ray_caster, ray_hits)) = rays.iter().next() else {
+        return;
+    };
+    let Some(hit) = ray_hits.first() else {
+        *visibility = Visibility::Hidden;
+        ghost.valid = false;
+        return;
+    };
+
+    let Some(building) = catalog
+        .get()
+        .and_then(|catalog| catalog.buildings().get(build_mode.selected))
+    else {
+        return;
+    };
+    let point = ray_caster.global_origin() + *ray_caster.global_direction() * hit.distance;
+    let snapped = (point.xz() / GRID_SIZE).round() * GRID_SIZE;
+    let rotation = Quat::from_rotation_y(build_mode.quarter_turns as f32 * FRAC_PI_2);
+
+    // Rests on the lowest corner of its footprint, sinking into slopes
+    let half_extents = (rotation * building.size / 2.0).xz().abs();
+    let (ground, ground_step) = match &heightmap {
+        Some(heightmap) => {
+            let corners = [
+                snapped + half_extents,
+                snapped - half_extents,
+                snapped + Vec2::new(half_extents.x, -half_extents.y),
+                snapped + Vec2::new(-half_extents.x, half_extents.y),
+            ]
+            .map(|corner| heightmap.height_at(corner.x, corner.y));
+            let lowest = corners.into_iter().fold(f32::INFINITY, f32::min);
+            let highest = corners.into_iter().fold(f32::NEG_INFINITY, f32::max);
+            (lowest, highest - lowest)
+        }
+        None => (point.y, 0.0),
+    };
+
+    // The ghost mesh is centered, so lift it onto the ground
+    *transform = Transform::from_xyz(snapped.x, ground + building.size.y / 2.0, snapped.y)
+        .with_rotation(rotation);
+    *visibility = Visibility::Inherited;
+
+    let test_shape = Collider::cuboid(
+        building.size.x,
+        building.size.y - GROUND_TOLERANCE * 2.0,
+        building.size.z,
+    );
+    let blocked = !spatial_query
+        .shape_intersections(
+            &test_shape,
+            transform.translation + Vec3::Y * GROUND_TOLERANCE,
+            rotation,
+            // The ground is checked for unevenness instead
+            &SpatialQueryFilter::from_excluded_entities(terrain.iter()),
+        )
+        .is_empty();
+    ghost.valid = !blocked
+        && ground_step <= MAX_GROUND_STEP
+        && unlocked.building_unlocked(building)
+        && game_resources.can_afford(&building.cost);
+
+    let target = if ghost.valid {
+        &ghost_materials.valid
+    } else {
+        &ghost_materials.invalid
+    };
+    if material.0 != *target {
+        material.0 = target.clone();
+    }
+}
+
+fn place_building(
+    mut interaction: On<PlayerInteraction>,
+    mut commands: Commands,
+    ghost: Query<(&Ghost, &Transform)>,
+    build_mode: Res<BuildMode>,
+    catalog: Catalog,
+    mut treasury: Treasury,
+    unlocked: UnlockedFeatures,
+    assets: Res<BuildingAssets>,
+) {
+    if !build_mode.active {
+        return;
+    }
+    // Placing consumes the interaction, so it only happens once
+    interaction.propagate(false);
+    let Ok((ghost, transform)) = ghost.single() else {
+        return;
+    };
+    if !ghost.valid {
+        return;
+    }
+    let Some(building) = catalog
+        .get()
+        .and_then(|catalog| catalog.buildings().get(build_mode.selected))
+    else {
+        return;
+    };
+    if !unlocked.building_unlocked(building) {
+        warn!(
+            "Can't build {}: requires researching {}",
+            building.name,
+            building.requires.join(", ")
+        );
+        return;
+    }
+    if !assets.contains(&building.id) {
+        warn!("Can't build {}: its assets are not loaded", building.name);
+        return;
+    }
+    if let Err(error) = treasury.try_spend(&building.cost) {
+        warn!("Can't build {}: {error}", building.name);
+        return;
+    }
+
+    let ground = transform.translation - Vec3::Y * building.size.y / 2.0;
+    spawn_building(
+        &mut commands,
+        &assets,
+        building,
+        Transform::from_translation(ground).with_rotation(transform.rotation),
+    );
+}
@@ -0,0 +1,272 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+/// Length of a full in-game day, night included.
+pub const DAY_LENGTH: Duration = Duration::from_secs(240);
+
+/// Time of day a new game starts at, shortly after sunrise.
+const START_TIME: f32 = 0.1;
+
+/// Horizontal direction the sun and moon travel along.
+const SUN_AZIMUTH: f32 = 0.54;
+
+/// Keeps the sun slightly above the horizon to avoid endless shadows.
+const MIN_SUN_ELEVATION: f32 = 0.12;
+
+/// Lighting at specific times of day, sorted by `time`.
+pub const KEYFRAMES: &[LightingKeyframe] = &[
+    LightingKeyframe {
+        time: 0.0,
+        sun_color: Color::srgb(1.0, 0.62, 0.4),
+        illuminance: 2_500.0,
+        fog_color: Color::srgb(0.55, 0.45, 0.5),
+        fog_light_color: Color::srgb(1.0, 0.7, 0.45),
+        sky_color: Color::srgb(0.42, 0.48, 0.7),
+    },
+    LightingKeyframe {
+        time: 0.12,
+        sun_color: Color::srgb(0.98, 0.95, 0.82),
+        illuminance: 10_000.0,
+        fog_color: Color::srgb(0.35, 0.48, 0.66),
+        fog_light_color: Color::srgb(1.0, 0.95, 0.85),
+        sky_color: Color::srgb(0.25, 0.45, 0.82),
+    },
+    LightingKeyframe {
+        time: 0.5,
+        sun_color: Color::srgb(0.98, 0.95, 0.82),
+        illuminance: 10_000.0,
+        fog_color: Color::srgb(0.35, 0.48, 0.66),
+        fog_light_color: Color::srgb(1.0, 0.95, 0.85),
+        sky_color: Color::srgb(0.25, 0.45, 0.82),
+    },
+    LightingKeyframe {
+        time: 0.6,
+        sun_color: Color::srgb(1.0, 0.5, 0.3),
+        illuminance: 2_000.0,
+        fog_color: Color::srgb(0.6, 0.38, 0.35),
+        fog_light_color: Color::srgb(1.0, 0.55, 0.3),
+        sky_color: Color::srgb(0.32, 0.3, 0.52),
+    },
+    LightingKeyframe {
+        time: 0.7,
+        sun_color: Color::srgb(0.55, 0.62, 0.9),
+        illuminance: 300.0,
+        fog_color: Color::srgb(0.05, 0.07, 0.14),
+        fog_light_color: Color::srgb(0.35, 0.4, 0.6),
+        sky_color: Color::srgb(0.02, 0.03, 0.08),
+    },
+    LightingKeyframe {
+        time: 0.92,
+        sun_color: Color::srgb(0.55, 0.62, 0.9),
+        illuminance: 300.0,
+        fog_color: Color::srgb(0.05, 0.07, 0.14),
+        fog_light_color: Color::srgb(0.35, 0.4, 0.6),
+        sky_color: Color::srgb(0.02, 0.03, 0.08),
+    },
+];
+
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DayNightCycle>()
+            .add_systems(OnEnter(GameState::InGame), reset_day_night_cycle)
+            .add_systems(
+                Update,
+                (advance_time_of_day, (animate_sun, animate_fog))
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Marks the directional light that is moved by the [`DayNightCycle`].
+#[derive(Component)]
+pub struct Sun;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum DayPhase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+impl DayPhase {
+    /// Time of day at which this phase begins.
+    pub fn start(self) -> f32 {
+        match self {
+            DayPhase::Dawn => 0.0,
+            DayPhase::Day => 0.08,
+            DayPhase::Dusk => 0.55,
+            DayPhase::Night => 0.65,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            DayPhase::Dawn => DayPhase::Day,
+            DayPhase::Day => DayPhase::Dusk,
+            DayPhase::Dusk => DayPhase::Night,
+            DayPhase::Night => DayPhase::Dawn,
+        }
+    }
+
+    pub fn at(time_of_day: f32) -> Self {
+        let time_of_day = time_of_day.rem_euclid(1.0);
+        [DayPhase::Night, DayPhase::Dusk, DayPhase::Day]
+            .into_iter()
+            .find(|phase| time_of_day >= phase.start())
+            .unwrap_or(DayPhase::Dawn)
+    }
+}
+
+/// Triggered whenever the [`DayNightCycle`] enters a new phase, e.g. when night falls.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DayPhaseChanged {
+    pub phase: DayPhase,
+    pub previous: DayPhase,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LightingKeyframe {
+    /// Time of day in `0.0..1.0`.
+    pub time: f32,
+    pub sun_color: Color,
+    pub illuminance: f32,
+    pub fog_color: Color,
+    pub fog_light_color: Color,
+    /// Color of the sky straight up, it fades into the fog color towards the horizon.
+    pub sky_color: Color,
+}
+
+impl LightingKeyframe {
+    fn mix(&self, other: &Self, factor: f32) -> Self {
+        Self {
+            time: self.time.lerp(other.time, factor),
+            sun_color: self.sun_color.mix(&other.sun_color, factor),
+            illuminance: self.illuminance.lerp(other.illuminance, factor),
+            fog_color: self.fog_color.mix(&other.fog_color, factor),
+            fog_light_color: self.fog_light_color.mix(&other.fog_light_color, factor),
+            sky_color: self.sky_color.mix(&other.sky_color, factor),
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct DayNightCycle {
+    /// Progress through the current day, `0.0` is the beginning of dawn.
+    pub time_of_day: f32,
+    pub day_length: Duration,
+    phase: DayPhase,
+}
+
+impl Default for DayNightCycle {
+    fn default() -> Self {
+        Self {
+            time_of_day: START_TIME,
+            day_length: DAY_LENGTH,
+            phase: DayPhase::at(START_TIME),
+        }
+    }
+}
+
+impl DayNightCycle {
+    /// The phase of the last emitted [`DayPhaseChanged`].
+    pub fn phase(&self) -> DayPhase {
+        self.phase
+    }
+
+    /// Jumps to `time_of_day` without any [`DayPhaseChanged`], e.g. when loading a save.
+    pub fn set_time_of_day(&mut self, time_of_day: f32) {
+        self.time_of_day = time_of_day.rem_euclid(1.0);
+        self.phase = DayPhase::at(self.time_of_day);
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.time_of_day =
+            (self.time_of_day + delta.as_secs_f32() / self.day_length.as_secs_f32()).fract();
+    }
+
+    /// Steps the phase once towards the phase of the current time of day.
+    /// Call repeatedly so no phase is skipped on large time steps.
+    pub fn next_phase_change(&mut self) -> Option<DayPhaseChanged> {
+        if self.phase == DayPhase::at(self.time_of_day) {
+            return None;
+        }
+        let previous = self.phase;
+        self.phase = previous.next();
+        Some(DayPhaseChanged {
+            phase: self.phase,
+            previous,
+        })
+    }
+
+    /// Lighting interpolated between the surrounding [`KEYFRAMES`].
+    pub fn lighting(&self) -> LightingKeyframe {
+        let next_index = KEYFRAMES
+            .iter()
+            .position(|keyframe| keyframe.time > self.time_of_day)
+            .unwrap_or(0);
+        let previous = &KEYFRAMES[(next_index + KEYFRAMES.len() - 1) % KEYFRAMES.len()];
+        let next = &KEYFRAMES[next_index];
+
+        let span = (next.time - previous.time).rem_euclid(1.0);
+        let progress = (self.time_of_day - previous.time).rem_euclid(1.0);
+        let factor = if span > 0.0 { progress / span } else { 0.0 };
+        previous.mix(next, factor)
+    }
+
+    /// Rotation of the [`Sun`]. The moon takes its place at night, so the
+    /// light always shines from above.
+    pub fn sun_rotation(&self) -> Quat {
+        let night = DayPhase::Night.start();
+        let arc = if self.time_of_day < night {
+            self.time_of_day / night
+        } else {
+            (self.time_of_day - night) / (1.0 - night)
+        };
+        let elevation = (arc * PI).clamp(MIN_SUN_ELEVATION, PI - MIN_SUN_ELEVATION);
+        Quat::from_rotation_y(SUN_AZIMUTH) * Quat::from_rotation_x(-elevation)
+    }
+}
+
+fn reset_day_night_cycle(mut cycle: ResMut<DayNightCycle>) {
+    *cycle = DayNightCycle {
+        day_length: cycle.day_length,
+        ..default()
+    };
+}
+
+fn advance_time_of_day(mut commands: Commands, mut cycle: ResMut<DayNightCycle>, time: Res<Time>) {
+    cycle.advance(time.delta());
+    while let Some(phase_changed) = cycle.next_phase_change() {
+        commands.trigger(phase_changed);
+    }
+}
+
+fn animate_sun(
+    mut sun: Query<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+    cycle: Res<DayNightCycle>,
+) {
+    let lighting = cycle.lighting();
+    for (mut light, mut transform) in sun.iter_mut() {
+        light.color = lighting.sun_color;
+        light.illuminance = lighting.illuminance;
+        transform.rotation = cycle.sun_rotation();
+    }
+}
+
+fn animate_fog(mut fogs: Query<&mut DistanceFog>, cycle: Res<DayNightCycle>) {
+    let lighting = cycle.lighting();
+    for mut fog in fogs.iter_mut() {
+        fog.color = lighting.fog_color;
+        // Keep the alpha, it controls how strongly the sun shines through the fog
+        fog.directional_light_color = lighting
+            .fog_light_color
+            .with_alpha(fog.directional_light_color.alpha());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, time::Duration};
+use std::time::Duration;
 
 use bevy::{
     ecs::{lifecycle::HookContext, world::DeferredWorld},
@@ -66,47 +66,6 @@ where
     }
 }
 
-#[derive(Component, Clone, Debug)]
-#[component(on_add = Self::on_add)]
-pub struct DelayRemove<T>
-where
-    T: Component,
-{
-    timer: Timer,
-    component: PhantomData<T>,
-}
-
-impl<T> DelayRemove<T>
-where
-    T: Component,
-{
-    pub fn new(delay: Duration) -> Self {
-        Self {
-            timer: Timer::new(delay, TimerMode::Once),
-            component: PhantomData,
-        }
-    }
-
-    fn on_add(mut world: DeferredWorld, hook_context: HookContext) {
-        let delay_insert_timer = world
-            .get::<DelayRemove<T>>(hook_context.entity)
-            .unwrap()
-            .timer
-            .to_owned();
-
-        world
-            .commands()
-            .entity(hook_context.entity)
-            .insert((DelayObserver(delay_insert_timer.clone()),))
-            .observe(move |delay: On<DelayEvent>, mut commands: Commands| {
-                commands
-                    .entity(delay.event_target())
-                    .remove::<T>()
-                    .remove::<DelayRemove<T>>();
-            });
-    }
-}
-
 fn delay_component_system(
     mut commands: Commands,
     mut query: Query<(Entity, &mut DelayObserver)>,
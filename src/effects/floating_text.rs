@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{GameState, constants::fonts};
+
+/// Time a floating text is shown for.
+const LIFETIME: Duration = Duration::from_millis(1200);
+
+/// Distance a floating text rises over its lifetime in world units.
+const RISE: f32 = 1.0;
+
+/// Shows [`FloatingText`]s over the world.
+pub struct FloatingTextPlugin;
+
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(spawn_floating_text).add_systems(
+            Update,
+            update_floating_text.run_if(in_state(GameState::InGame)),
+        );
+    }
+}
+
+/// Shows `text` at `position` in the world, drifting up and fading out, e.g. for resources
+/// gained or damage dealt.
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct FloatingText {
+    pub text: String,
+    pub position: Vec3,
+    pub color: Color,
+}
+
+impl FloatingText {
+    pub fn new(text: impl Into<String>, position: Vec3, color: Color) -> Self {
+        Self {
+            text: text.into(),
+            position,
+            color,
+        }
+    }
+}
+
+/// UI text following its point in the world.
+#[derive(Component, Debug)]
+struct FloatingLabel {
+    origin: Vec3,
+    color: Color,
+    age: Timer,
+}
+
+fn spawn_floating_text(
+    text: On<FloatingText>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn((
+        Name::new(format!("Floating Text {}", text.text)),
+        FloatingLabel {
+            origin: text.position,
+            color: text.color,
+            age: Timer::new(LIFETIME, TimerMode::Once),
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        Text::new(text.text.clone()),
+        TextFont {
+            font: asset_server.load(fonts::REGULAR),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(text.color),
+        TextLayout::new_with_linebreak(LineBreak::NoWrap),
+        // Shown once it is placed over its point
+        Visibility::Hidden,
+        Pickable::IGNORE,
+        DespawnOnExit(GameState::InGame),
+    ));
+}
+
+fn update_floating_text(
+    mut commands: Commands,
+    mut labels: Query<(
+        Entity,
+        &mut FloatingLabel,
+        &mut Node,
+        &mut TextColor,
+        &mut Visibility,
+        &ComputedNode,
+    )>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    ui_scale: Res<UiScale>,
+    time: Res<Time>,
+) {
+    let (camera, camera_transform) = *camera;
+    for (entity, mut label, mut node, mut color, mut visibility, computed) in labels.iter_mut() {
+        label.age.tick(time.delta());
+        if label.age.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = label.age.fraction();
+        let position = label.origin + Vec3::Y * RISE * progress;
+        // Hidden while its point is behind the camera
+        let Ok(viewport) = camera.world_to_viewport(camera_transform, position) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        // The viewport is in logical pixels, UI values are scaled on top of that
+        let viewport = viewport / ui_scale.0;
+        let size = computed.size() * computed.inverse_scale_factor();
+        node.left = px(viewport.x - size.x / 2.0);
+        node.top = px(viewport.y - size.y / 2.0);
+        *visibility = Visibility::Inherited;
+
+        // Fades out towards the end
+        color.0 = label.color.with_alpha(1.0 - progress.powi(3));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::camera::RenderTargetInfo;
+
+    use super::*;
+    use crate::testing::headless_app;
+
+    #[test]
+    fn labels_follow_their_point_at_any_ui_scale() {
+        let mut app = headless_app();
+        app.init_asset::<Font>()
+            .insert_state(GameState::InGame)
+            .insert_resource(UiScale(4.0))
+            .add_plugins(FloatingTextPlugin);
+
+        let mut camera = Camera::default();
+        camera.computed.target_info = Some(RenderTargetInfo {
+            physical_size: UVec2::new(1280, 720),
+            scale_factor: 1.0,
+        });
+        camera.computed.clip_from_view =
+            Mat4::perspective_infinite_reverse_rh(FRAC_PI_4, 1280.0 / 720.0, 0.1);
+        let camera_transform = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        app.world_mut()
+            .spawn((Camera3d::default(), camera.clone(), camera_transform));
+
+        app.world_mut().trigger(FloatingText::new(
+            "+1 Wood",
+            Vec3::new(2.0, 1.0, 0.0),
+            Color::WHITE,
+        ));
+        app.update();
+        app.update();
+
+        let mut labels = app.world_mut().query::<(&FloatingLabel, &Node)>();
+        let (label, node) = labels.single(app.world()).unwrap();
+        let position = label.origin + Vec3::Y * RISE * label.age.fraction();
+        let viewport = camera
+            .world_to_viewport(&camera_transform, position)
+            .unwrap()
+            / 4.0;
+        // Nothing is laid out headless, so the label has no size to center
+        assert_eq!(node.left, px(viewport.x));
+        assert_eq!(node.top, px(viewport.y));
+    }
+}
@@ -1,78 +1,364 @@
-use bevy::{
-    ecs::{lifecycle::HookContext, world::DeferredWorld},
-    prelude::*,
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
 };
 
-#[derive(Component, Clone, Debug)]
-#[component(on_remove = Self::on_remove)]
-#[component(on_insert= Self::on_insert)]
-#[component(on_replace = Self::on_replace)]
-pub struct MeshMaterialOverride<T, M>
-where
-    T: Material,
-    M: Material,
-{
-    pub material: Handle<T>,
-    previous_material: Handle<M>,
+use bevy::prelude::*;
+
+use crate::leaf_material::LeafMaterial;
+
+/// Temporarily swaps the material of meshes, e.g. to flash them on a hit or to highlight a
+/// selection. Overrides stack, the one with the highest priority is shown and the original
+/// material comes back once all of them are removed.
+pub struct MaterialOverridePlugin;
+
+impl Plugin for MaterialOverridePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            OverridableMaterials::default()
+                .with::<StandardMaterial>()
+                .with::<LeafMaterial>(),
+        )
+        .add_systems(Update, expire_material_overrides);
+    }
+}
+
+/// Identifies an override in the stack of an entity, to replace or remove it again. Each caller
+/// allocates its own with [`MaterialOverrideId::unique`], so they can't touch each other's
+/// overrides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialOverrideId(u64);
+
+impl MaterialOverrideId {
+    pub fn unique() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Handle to a material of any type, which can be put on a mesh and taken off again.
+#[derive(Debug, Clone)]
+pub struct AnyMaterial {
+    handle: UntypedHandle,
+    apply: fn(&mut EntityWorldMut, &UntypedHandle),
+    clear: fn(&mut EntityWorldMut),
 }
 
-impl<T, M> MeshMaterialOverride<T, M>
-where
-    T: Material,
-    M: Material,
-{
-    pub fn new(material: Handle<T>) -> Self {
+impl PartialEq for AnyMaterial {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl<M: Material> From<Handle<M>> for AnyMaterial {
+    fn from(handle: Handle<M>) -> Self {
         Self {
-            material,
-            previous_material: Handle::<M>::default(),
+            handle: handle.untyped(),
+            apply: |entity: &mut EntityWorldMut, handle: &UntypedHandle| {
+                entity.insert(MeshMaterial3d::<M>(handle.clone().typed()));
+            },
+            clear: |entity: &mut EntityWorldMut| {
+                entity.remove::<MeshMaterial3d<M>>();
+            },
         }
     }
+}
 
-    fn on_remove(mut world: DeferredWorld, hook_context: HookContext) {
-        let Some(override_mat) = world
-            .get::<MeshMaterialOverride<T, M>>(hook_context.entity)
-            .cloned()
-        else {
-            return;
-        };
+/// A material shown instead of a mesh's own while it has the highest priority.
+#[derive(Debug, Clone)]
+pub struct MaterialOverride {
+    pub id: MaterialOverrideId,
+    /// Overrides with a higher priority are shown over lower ones, the latest wins a tie.
+    pub priority: i32,
+    pub material: AnyMaterial,
+    /// Removes the override once it runs out.
+    pub lifetime: Option<Timer>,
+}
 
-        if override_mat.previous_material == Handle::<M>::default() {
-            return;
+impl MaterialOverride {
+    pub fn new(id: MaterialOverrideId, material: impl Into<AnyMaterial>) -> Self {
+        Self {
+            id,
+            priority: 0,
+            material: material.into(),
+            lifetime: None,
         }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
 
-        world
+    /// Removes the override after `duration`, e.g. for a short flash.
+    pub fn lasting(mut self, duration: Duration) -> Self {
+        self.lifetime = Some(Timer::new(duration, TimerMode::Once));
+        self
+    }
+}
+
+/// Overrides active on a mesh and the material it had before them. Added with the first
+/// override and removed with the last one.
+#[derive(Component, Debug)]
+pub struct MaterialOverrides {
+    original: AnyMaterial,
+    stack: Vec<MaterialOverride>,
+}
+
+impl MaterialOverrides {
+    /// The material that should be shown, the top override or the original.
+    pub fn shown(&self) -> &AnyMaterial {
+        // The last of equal maximums is the latest
+        self.stack
+            .iter()
+            .max_by_key(|material_override| material_override.priority)
+            .map_or(&self.original, |material_override| {
+                &material_override.material
+            })
+    }
+}
+
+/// Material types whose [`MeshMaterial3d`] can be overridden, each with a function taking the
+/// material off an entity that has one.
+#[derive(Resource, Default)]
+struct OverridableMaterials(Vec<fn(&EntityWorldMut) -> Option<AnyMaterial>>);
+
+impl OverridableMaterials {
+    fn with<M: Material>(mut self) -> Self {
+        self.0.push(|entity: &EntityWorldMut| {
+            entity
+                .get::<MeshMaterial3d<M>>()
+                .map(|material| material.0.clone().into())
+        });
+        self
+    }
+}
+
+pub trait MaterialOverrideCommands {
+    /// Shows the material of `material_override` on this mesh, unless a higher priority override
+    /// is active. Replaces an override with the same id. Does nothing to entities without a
+    /// material of an overridable type.
+    fn push_material_override(&mut self, material_override: MaterialOverride) -> &mut Self;
+
+    /// Removes the override with `id`, restoring the next one or the original material.
+    fn remove_material_override(&mut self, id: MaterialOverrideId) -> &mut Self;
+}
+
+impl MaterialOverrideCommands for EntityCommands<'_> {
+    fn push_material_override(&mut self, material_override: MaterialOverride) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            if !entity.contains::<MaterialOverrides>() {
+                let Some(original) = entity
+                    .world()
+                    .get_resource::<OverridableMaterials>()
+                    .and_then(|materials| materials.0.iter().find_map(|capture| capture(&entity)))
+                else {
+                    return;
+                };
+                entity.insert(MaterialOverrides {
+                    original,
+                    stack: Vec::new(),
+                });
+            }
+            update_overrides(&mut entity, |stack| {
+                stack.retain(|existing| existing.id != material_override.id);
+                stack.push(material_override);
+            });
+        })
+    }
+
+    fn remove_material_override(&mut self, id: MaterialOverrideId) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            update_overrides(&mut entity, |stack| {
+                stack.retain(|existing| existing.id != id);
+            });
+        })
+    }
+}
+
+/// Changes the stack of overrides and swaps the shown material if the top one changed.
+fn update_overrides(entity: &mut EntityWorldMut, change: impl FnOnce(&mut Vec<MaterialOverride>)) {
+    let Some(mut overrides) = entity.get_mut::<MaterialOverrides>() else {
+        return;
+    };
+    let before = overrides.shown().clone();
+    change(&mut overrides.stack);
+    let after = overrides.shown().clone();
+    let finished = overrides.stack.is_empty();
+
+    if after != before {
+        // The materials can be of different types, so the old one has to go first
+        (before.clear)(entity);
+        (after.apply)(entity, &after.handle);
+    }
+    if finished {
+        entity.remove::<MaterialOverrides>();
+    }
+}
+
+fn expire_material_overrides(
+    mut commands: Commands,
+    mut overrides: Query<(Entity, &mut MaterialOverrides)>,
+    time: Res<Time>,
+) {
+    for (entity, mut overrides) in overrides.iter_mut() {
+        for material_override in overrides.stack.iter_mut() {
+            let Some(lifetime) = &mut material_override.lifetime else {
+                continue;
+            };
+            if lifetime.tick(time.delta()).just_finished() {
+                commands
+                    .entity(entity)
+                    .remove_material_override(material_override.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{advance, headless_app};
+
+    struct Materials {
+        original: Handle<StandardMaterial>,
+        flash: Handle<StandardMaterial>,
+        highlight: Handle<StandardMaterial>,
+        leaves: Handle<LeafMaterial>,
+    }
+
+    fn override_app() -> (App, Materials) {
+        let mut app = headless_app();
+        app.add_plugins(MaterialOverridePlugin);
+        let mut standard = app.world_mut().resource_mut::<Assets<StandardMaterial>>();
+        let original = standard.add(StandardMaterial::default());
+        let flash = standard.add(StandardMaterial::default());
+        let highlight = standard.add(StandardMaterial::default());
+        let leaves = app
+            .world_mut()
+            .resource_mut::<Assets<LeafMaterial>>()
+            .add(LeafMaterial {
+                base: StandardMaterial::default(),
+                extension: default(),
+            });
+        (
+            app,
+            Materials {
+                original,
+                flash,
+                highlight,
+                leaves,
+            },
+        )
+    }
+
+    fn push(app: &mut App, entity: Entity, material_override: MaterialOverride) {
+        app.world_mut()
             .commands()
-            .entity(hook_context.entity)
-            .insert(MeshMaterial3d(override_mat.previous_material));
-    }
-    fn on_insert(mut world: DeferredWorld, hook_context: HookContext) {
-        let Some(mat) = world.get::<MeshMaterial3d<M>>(hook_context.entity).cloned() else {
-            return;
-        };
-        let Some(mut override_mat) =
-            world.get_mut::<MeshMaterialOverride<T, M>>(hook_context.entity)
-        else {
-            return;
-        };
-
-        override_mat.previous_material = mat.0;
-        let new_mat = override_mat.material.clone();
-        world
+            .entity(entity)
+            .push_material_override(material_override);
+        app.world_mut().flush();
+    }
+
+    fn remove(app: &mut App, entity: Entity, id: MaterialOverrideId) {
+        app.world_mut()
             .commands()
-            .entity(hook_context.entity)
-            .insert(MeshMaterial3d(new_mat));
-    }
-
-    fn on_replace(mut world: DeferredWorld, hook_context: HookContext) {
-        let Some(override_mat) = world
-            .get::<MeshMaterialOverride<T, M>>(hook_context.entity)
-            .cloned()
-        else {
-            return;
-        };
-        let Some(mut mat) = world.get_mut::<MeshMaterial3d<M>>(hook_context.entity) else {
-            return;
-        };
-        mat.0 = override_mat.previous_material;
+            .entity(entity)
+            .remove_material_override(id);
+        app.world_mut().flush();
+    }
+
+    fn standard(app: &App, entity: Entity) -> Option<Handle<StandardMaterial>> {
+        app.world()
+            .get::<MeshMaterial3d<StandardMaterial>>(entity)
+            .map(|material| material.0.clone())
+    }
+
+    fn leaves(app: &App, entity: Entity) -> Option<Handle<LeafMaterial>> {
+        app.world()
+            .get::<MeshMaterial3d<LeafMaterial>>(entity)
+            .map(|material| material.0.clone())
+    }
+
+    #[test]
+    fn highest_priority_is_shown_and_original_restored() {
+        let (mut app, materials) = override_app();
+        let entity = app
+            .world_mut()
+            .spawn(MeshMaterial3d(materials.original.clone()))
+            .id();
+        let highlight = MaterialOverrideId::unique();
+        let flash = MaterialOverrideId::unique();
+
+        push(
+            &mut app,
+            entity,
+            MaterialOverride::new(highlight, materials.highlight.clone()),
+        );
+        assert_eq!(standard(&app, entity), Some(materials.highlight.clone()));
+
+        push(
+            &mut app,
+            entity,
+            MaterialOverride::new(flash, materials.flash.clone()).with_priority(10),
+        );
+        assert_eq!(standard(&app, entity), Some(materials.flash.clone()));
+
+        // Replacing the highlight doesn't bring it over the flash
+        push(
+            &mut app,
+            entity,
+            MaterialOverride::new(highlight, materials.highlight.clone()),
+        );
+        assert_eq!(standard(&app, entity), Some(materials.flash.clone()));
+
+        remove(&mut app, entity, flash);
+        assert_eq!(standard(&app, entity), Some(materials.highlight.clone()));
+        remove(&mut app, entity, highlight);
+        assert_eq!(standard(&app, entity), Some(materials.original.clone()));
+        assert!(!app.world().entity(entity).contains::<MaterialOverrides>());
+    }
+
+    #[test]
+    fn material_types_can_be_mixed() {
+        let (mut app, materials) = override_app();
+        let tree = app
+            .world_mut()
+            .spawn(MeshMaterial3d(materials.leaves.clone()))
+            .id();
+        let flash = MaterialOverrideId::unique();
+
+        push(
+            &mut app,
+            tree,
+            MaterialOverride::new(flash, materials.flash.clone()),
+        );
+        assert_eq!(standard(&app, tree), Some(materials.flash.clone()));
+        assert_eq!(leaves(&app, tree), None);
+
+        remove(&mut app, tree, flash);
+        assert_eq!(leaves(&app, tree), Some(materials.leaves.clone()));
+        assert_eq!(standard(&app, tree), None);
+    }
+
+    #[test]
+    fn lasting_overrides_expire() {
+        let (mut app, materials) = override_app();
+        let entity = app
+            .world_mut()
+            .spawn(MeshMaterial3d(materials.original.clone()))
+            .id();
+
+        push(
+            &mut app,
+            entity,
+            MaterialOverride::new(MaterialOverrideId::unique(), materials.leaves.clone())
+                .lasting(Duration::from_millis(300)),
+        );
+        assert_eq!(leaves(&app, entity), Some(materials.leaves.clone()));
+
+        advance(&mut app, Duration::from_millis(600));
+        assert_eq!(leaves(&app, entity), None);
+        assert_eq!(standard(&app, entity), Some(materials.original.clone()));
     }
 }
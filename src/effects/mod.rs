@@ -1,2 +1,4 @@
 pub mod delay_component;
+pub mod floating_text;
 pub mod mesh_material_override;
+pub mod particles;
@@ -0,0 +1,241 @@
+use std::{f32::consts::PI, ops::RangeInclusive, time::Duration};
+
+use bevy::{
+    light::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{GameState, world::Heightmap};
+
+/// Simulates short-lived particles on the CPU, each its own small mesh entity. Meant for a few
+/// dozen particles at a time, e.g. the chips flying off a hit.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleAssets>()
+            .add_observer(spawn_particles)
+            .add_systems(Update, update_particles.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// Look and motion of a burst of particles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleEffect {
+    WoodChips,
+    StoneDust,
+    /// Falling slowly and swaying, e.g. shaken out of a tree.
+    Leaves,
+}
+
+impl ParticleEffect {
+    pub const ALL: [ParticleEffect; 3] = [
+        ParticleEffect::WoodChips,
+        ParticleEffect::StoneDust,
+        ParticleEffect::Leaves,
+    ];
+
+    fn style(self) -> ParticleStyle {
+        match self {
+            ParticleEffect::WoodChips => ParticleStyle {
+                count: 6..=10,
+                speed: 2.0..=4.0,
+                spread: 0.8,
+                gravity: 9.8,
+                drag: 1.0,
+                sway: 0.0,
+                lifetime: Duration::from_millis(700),
+                size: 0.06,
+                color: Color::srgb_u8(176, 130, 80),
+            },
+            ParticleEffect::StoneDust => ParticleStyle {
+                count: 10..=14,
+                speed: 1.0..=2.5,
+                spread: 1.2,
+                gravity: 2.0,
+                drag: 3.0,
+                sway: 0.0,
+                lifetime: Duration::from_millis(900),
+                size: 0.05,
+                color: Color::srgb_u8(150, 146, 140),
+            },
+            ParticleEffect::Leaves => ParticleStyle {
+                count: 3..=5,
+                speed: 0.5..=1.0,
+                spread: PI,
+                gravity: 1.0,
+                drag: 2.0,
+                sway: 0.6,
+                lifetime: Duration::from_secs(3),
+                size: 0.08,
+                color: Color::srgb_u8(86, 120, 50),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ParticleStyle {
+    count: RangeInclusive<u32>,
+    /// Initial speed in units per second.
+    speed: RangeInclusive<f32>,
+    /// Largest angle in radians between a particle's initial direction and the burst's.
+    spread: f32,
+    gravity: f32,
+    /// Fraction of the velocity lost per second.
+    drag: f32,
+    /// Strength of the side to side motion.
+    sway: f32,
+    lifetime: Duration,
+    size: f32,
+    color: Color,
+}
+
+/// Spawns a burst of particles at `position`, flying off along `direction`, e.g. the surface
+/// normal at a ray hit.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct SpawnParticles {
+    pub effect: ParticleEffect,
+    pub position: Vec3,
+    pub direction: Vec3,
+}
+
+impl SpawnParticles {
+    pub fn new(effect: ParticleEffect, position: Vec3, direction: Vec3) -> Self {
+        Self {
+            effect,
+            position,
+            direction,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+struct Particle {
+    velocity: Vec3,
+    /// Rotation per second around its axis.
+    spin: Vec3,
+    gravity: f32,
+    drag: f32,
+    sway: f32,
+    size: f32,
+    age: Timer,
+}
+
+#[derive(Resource)]
+struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    materials: Vec<(ParticleEffect, Handle<StandardMaterial>)>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Cuboid::from_length(1.0));
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            mesh,
+            materials: ParticleEffect::ALL
+                .into_iter()
+                .map(|effect| {
+                    let material = materials.add(StandardMaterial {
+                        base_color: effect.style().color,
+                        perceptual_roughness: 1.0,
+                        ..default()
+                    });
+                    (effect, material)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn spawn_particles(spawn: On<SpawnParticles>, mut commands: Commands, assets: Res<ParticleAssets>) {
+    let style = spawn.effect.style();
+    let Some((_, material)) = assets
+        .materials
+        .iter()
+        .find(|(effect, _)| *effect == spawn.effect)
+    else {
+        return;
+    };
+    let direction = Dir3::new(spawn.direction).unwrap_or(Dir3::Y);
+    // Turns directions around +Y into directions around the burst's
+    let towards = Quat::from_rotation_arc(Vec3::Y, *direction);
+
+    let mut rng = rand::rng();
+    for _ in 0..rng.random_range(style.count.clone()) {
+        let tilt = rng.random_range(0.0..=style.spread);
+        let turn = rng.random_range(0.0..2.0 * PI);
+        let velocity = towards
+            * Quat::from_rotation_y(turn)
+            * Quat::from_rotation_x(tilt)
+            * Vec3::Y
+            * rng.random_range(style.speed.clone());
+        let size = style.size * rng.random_range(0.6..=1.4);
+        commands.spawn((
+            Name::new(format!("Particle {:?}", spawn.effect)),
+            Particle {
+                velocity,
+                spin: Vec3::new(
+                    rng.random_range(-1.0..=1.0),
+                    rng.random_range(-1.0..=1.0),
+                    rng.random_range(-1.0..=1.0),
+                ) * 8.0,
+                gravity: style.gravity,
+                drag: style.drag,
+                sway: style.sway,
+                size,
+                age: Timer::new(style.lifetime, TimerMode::Once),
+            },
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(spawn.position)
+                .with_rotation(Quat::from_scaled_axis(Vec3::splat(turn)))
+                .with_scale(Vec3::splat(size)),
+            NotShadowCaster,
+            NotShadowReceiver,
+            DespawnOnExit(GameState::InGame),
+        ));
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform)>,
+    heightmap: Option<Res<Heightmap>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut particle, mut transform) in particles.iter_mut() {
+        particle.age.tick(time.delta());
+        if particle.age.is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let ground = heightmap.as_ref().map_or(f32::NEG_INFINITY, |heightmap| {
+            heightmap.height_at(transform.translation.x, transform.translation.z)
+        });
+        if transform.translation.y > ground {
+            particle.velocity.y -= particle.gravity * delta;
+            particle.velocity *= (1.0 - particle.drag * delta).max(0.0);
+            let sway = Vec3::new(
+                (particle.age.elapsed_secs() * 3.0).sin(),
+                0.0,
+                (particle.age.elapsed_secs() * 2.0).cos(),
+            ) * particle.sway;
+            transform.translation += (particle.velocity + sway) * delta;
+            transform.rotate(Quat::from_scaled_axis(particle.spin * delta));
+        } else {
+            // Settles on the ground
+            transform.translation.y = ground;
+        }
+
+        // Shrinks away over the second half of its life
+        let fade = ((1.0 - particle.age.fraction()) * 2.0).min(1.0);
+        transform.scale = Vec3::splat(particle.size * fade);
+    }
+}
@@ -0,0 +1,231 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use avian3d::prelude::{Collider, ColliderDisabled, RigidBody};
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    effects::floating_text::FloatingText,
+    game_resources::{ResourceKind, Treasury},
+    health::{Armor, DamageEvent, Dead, DespawnPolicy, Died, Health, Resistances, mitigate},
+    navigation::NavGrid,
+    world::{
+        CASTLE_SITE, Heightmap,
+        chunk::{CHUNK_SIZE, ChunkAnchor},
+    },
+};
+
+/// Movement speed of enemies in units per second.
+pub const ENEMY_SPEED: f32 = 2.5;
+
+pub const ENEMY_HEALTH: f32 = 30.0;
+
+/// Height of an enemy's origin above the ground, half the height of its capsule.
+pub const ENEMY_GROUND_OFFSET: f32 = 0.9;
+
+/// Gold earned for every defeated enemy.
+pub const ENEMY_BOUNTY: u32 = 2;
+
+/// Time a defeated enemy lies on the ground before it is despawned.
+const CORPSE_DURATION: Duration = Duration::from_secs(3);
+
+/// Height above an enemy's origin at which damage numbers are shown.
+const DAMAGE_TEXT_HEIGHT: f32 = 1.2;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EnemyAssets>()
+            .add_observer(add_enemy_visuals)
+            .add_observer(defeat_enemy)
+            .add_observer(damage_numbers)
+            .add_systems(
+                Update,
+                (plan_enemy_paths, follow_enemy_paths, topple_defeated)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Marker for invaders spawned by the [`WaveDirector`](crate::wave::WaveDirector).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[require(
+    Health = Health::new(ENEMY_HEALTH),
+    RigidBody = RigidBody::Kinematic,
+    Collider = Collider::capsule(0.3, 1.2),
+    // Loads the obstacles along its way
+    ChunkAnchor = ChunkAnchor::Radius(CHUNK_SIZE / 2.0),
+    DespawnPolicy = DespawnPolicy::AfterDelay(CORPSE_DURATION)
+)]
+pub struct Enemy {
+    /// Day of the wave this enemy belongs to.
+    pub wave: u32,
+}
+
+/// Remaining waypoints on the XZ plane towards the castle.
+#[derive(Component, Debug, Default)]
+pub struct EnemyPath {
+    waypoints: Vec<Vec2>,
+    /// [`NavGrid::revision`] the path was last checked against.
+    revision: u32,
+}
+
+/// Rotation a defeated enemy falls over to.
+#[derive(Component, Debug)]
+struct Toppling(Quat);
+
+#[derive(Resource)]
+struct EnemyAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for EnemyAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Capsule3d::new(0.3, 1.2));
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Srgba::hex("8c2f2f").unwrap().into(),
+                perceptual_roughness: 1.0,
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+/// Enemies are spawned without any visuals so the wave logic can run headless.
+fn add_enemy_visuals(add: On<Add, Enemy>, mut commands: Commands, assets: Res<EnemyAssets>) {
+    commands.entity(add.entity).insert((
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+    ));
+}
+
+fn defeat_enemy(
+    died: On<Died>,
+    mut commands: Commands,
+    enemies: Query<&Transform, With<Enemy>>,
+    mut treasury: Treasury,
+) {
+    let Ok(transform) = enemies.get(died.entity) else {
+        return;
+    };
+    treasury.add(ResourceKind::Gold, ENEMY_BOUNTY);
+    commands.trigger(FloatingText::new(
+        format!("+{ENEMY_BOUNTY} {}", ResourceKind::Gold),
+        transform.translation + Vec3::Y * DAMAGE_TEXT_HEIGHT,
+        Color::srgb_u8(240, 200, 80),
+    ));
+    commands
+        .entity(died.entity)
+        .insert((
+            Toppling(transform.rotation * Quat::from_rotation_x(-FRAC_PI_2)),
+            ColliderDisabled,
+        ))
+        .remove::<EnemyPath>();
+}
+
+fn damage_numbers(
+    damage: On<DamageEvent>,
+    mut commands: Commands,
+    enemies: Query<
+        (&Transform, Option<&Armor>, Option<&Resistances>),
+        (With<Enemy>, Without<Dead>),
+    >,
+) {
+    let Ok((transform, armor, resistances)) = enemies.get(damage.event_target()) else {
+        return;
+    };
+    let amount = mitigate(damage.amount, damage.kind, armor, resistances);
+    commands.trigger(FloatingText::new(
+        format!("{amount:.0}"),
+        transform.translation + Vec3::Y * DAMAGE_TEXT_HEIGHT,
+        Color::srgb_u8(230, 60, 40),
+    ));
+}
+
+/// Plans paths for new enemies and replans when an obstacle was placed on their way.
+fn plan_enemy_paths(
+    mut commands: Commands,
+    mut enemies: Query<(Entity, &Transform, Option<&mut EnemyPath>), (With<Enemy>, Without<Dead>)>,
+    nav_grid: Res<NavGrid>,
+) {
+    for (entity, transform, path) in enemies.iter_mut() {
+        if let Some(path) = &path {
+            if path.revision == nav_grid.revision() {
+                continue;
+            }
+            let blocked = path.waypoints.iter().any(|waypoint| {
+                nav_grid
+                    .cell_at(*waypoint)
+                    .is_some_and(|cell| !nav_grid.is_walkable(cell))
+            });
+            if !blocked {
+                continue;
+            }
+        }
+
+        let waypoints = nav_grid
+            .find_path(transform.translation.xz(), CASTLE_SITE)
+            // Walled in, walk straight at the castle
+            .unwrap_or_else(|| vec![CASTLE_SITE]);
+        let new_path = EnemyPath {
+            waypoints,
+            revision: nav_grid.revision(),
+        };
+        match path {
+            Some(mut path) => *path = new_path,
+            None => {
+                commands.entity(entity).insert(new_path);
+            }
+        }
+    }
+}
+
+fn follow_enemy_paths(
+    mut enemies: Query<(&mut Transform, &mut EnemyPath), With<Enemy>>,
+    nav_grid: Res<NavGrid>,
+    heightmap: Option<Res<Heightmap>>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut path) in enemies.iter_mut() {
+        // Paths that are still valid don't need to be checked again
+        path.revision = nav_grid.revision();
+
+        let mut step = ENEMY_SPEED * time.delta_secs();
+        while let Some(&waypoint) = path.waypoints.first() {
+            let position = transform.translation.xz();
+            let distance = position.distance(waypoint);
+            if distance > step {
+                let next = position.move_towards(waypoint, step);
+                transform.translation.x = next.x;
+                transform.translation.z = next.y;
+                let target = Vec3::new(waypoint.x, transform.translation.y, waypoint.y);
+                transform.look_at(target, Vec3::Y);
+                break;
+            }
+            transform.translation.x = waypoint.x;
+            transform.translation.z = waypoint.y;
+            step -= distance;
+            path.waypoints.remove(0);
+        }
+        if let Some(heightmap) = &heightmap {
+            transform.translation.y = heightmap
+                .height_at(transform.translation.x, transform.translation.z)
+                + ENEMY_GROUND_OFFSET;
+        }
+    }
+}
+
+fn topple_defeated(mut enemies: Query<(&mut Transform, &Toppling)>, time: Res<Time>) {
+    for (mut transform, toppling) in enemies.iter_mut() {
+        transform.rotation = transform
+            .rotation
+            .slerp(toppling.0, (8.0 * time.delta_secs()).min(1.0));
+    }
+}
@@ -1,20 +1,310 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Resource, Default)]
-pub struct GameResources {
+use crate::GameState;
+
+pub struct GameResourcesPlugin;
+
+impl Plugin for GameResourcesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameResources>()
+            .add_message::<ResourcesChanged>()
+            .add_systems(OnEnter(GameState::InGame), reset_game_resources);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum ResourceKind {
+    Wood,
+    Stone,
+    Gold,
+    Food,
+    Population,
+}
+
+impl ResourceKind {
+    pub const ALL: [ResourceKind; 5] = [
+        ResourceKind::Wood,
+        ResourceKind::Stone,
+        ResourceKind::Gold,
+        ResourceKind::Food,
+        ResourceKind::Population,
+    ];
+}
+
+impl Display for ResourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ResourceKind::Wood => "Wood",
+            ResourceKind::Stone => "Stone",
+            ResourceKind::Gold => "Gold",
+            ResourceKind::Food => "Food",
+            ResourceKind::Population => "Population",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for ResourceKind {
+    type Err = ();
+
+    /// Parses the name as shown by [`Display`].
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ResourceKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == name)
+            .ok_or(())
+    }
+}
+
+/// Amount of each [`ResourceKind`], used for prices and stock alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Cost {
     pub wood: u32,
     pub stone: u32,
     pub gold: u32,
+    pub food: u32,
+    pub population: u32,
+}
+
+impl Cost {
+    pub fn get(&self, kind: ResourceKind) -> u32 {
+        match kind {
+            ResourceKind::Wood => self.wood,
+            ResourceKind::Stone => self.stone,
+            ResourceKind::Gold => self.gold,
+            ResourceKind::Food => self.food,
+            ResourceKind::Population => self.population,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ResourceKind) -> &mut u32 {
+        match kind {
+            ResourceKind::Wood => &mut self.wood,
+            ResourceKind::Stone => &mut self.stone,
+            ResourceKind::Gold => &mut self.gold,
+            ResourceKind::Food => &mut self.food,
+            ResourceKind::Population => &mut self.population,
+        }
+    }
+
+    /// Kinds with a non-zero amount.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, u32)> + '_ {
+        ResourceKind::ALL
+            .into_iter()
+            .map(|kind| (kind, self.get(kind)))
+            .filter(|(_, amount)| *amount > 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Builds a cost from resource names, e.g. read from a data file.
+    /// Returns the first name that is no [`ResourceKind`].
+    pub fn from_names(amounts: impl IntoIterator<Item = (String, u32)>) -> Result<Self, String> {
+        let mut cost = Cost::default();
+        for (name, amount) in amounts {
+            let kind = name.parse::<ResourceKind>().map_err(|_| name)?;
+            *cost.get_mut(kind) = amount;
+        }
+        Ok(cost)
+    }
+}
+
+impl Display for Cost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (kind, amount)) in self.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{kind}: {amount}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsufficientResources {
+    pub missing: Cost,
+}
+
+impl Display for InsufficientResources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Not enough resources, missing {}", self.missing)
+    }
+}
+
+impl std::error::Error for InsufficientResources {}
+
+/// Written for every change of a single [`ResourceKind`] in [`GameResources`].
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourcesChanged {
+    pub kind: ResourceKind,
+    pub delta: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Resource, Default)]
+pub struct GameResources(Cost);
+
+impl GameResources {
+    /// Amount of every kind in stock.
+    pub fn stock(&self) -> Cost {
+        self.0
+    }
+
+    pub fn get(&self, kind: ResourceKind) -> u32 {
+        self.0.get(kind)
+    }
+
+    /// Adds `amount`, saturating at [`u32::MAX`]. Returns the amount actually added.
+    fn add(&mut self, kind: ResourceKind, amount: u32) -> u32 {
+        let stock = self.0.get_mut(kind);
+        let added = amount.min(u32::MAX - *stock);
+        *stock += added;
+        added
+    }
+
+    /// Removes `amount`, saturating at zero. Returns the amount actually removed.
+    fn remove(&mut self, kind: ResourceKind, amount: u32) -> u32 {
+        let stock = self.0.get_mut(kind);
+        let removed = amount.min(*stock);
+        *stock -= removed;
+        removed
+    }
+
+    pub fn can_afford(&self, cost: &Cost) -> bool {
+        self.missing(cost).is_empty()
+    }
+
+    /// What is lacking to pay `cost`.
+    pub fn missing(&self, cost: &Cost) -> Cost {
+        let mut missing = Cost::default();
+        for (kind, amount) in cost.iter() {
+            *missing.get_mut(kind) = amount.saturating_sub(self.get(kind));
+        }
+        missing
+    }
+
+    /// Pays `cost` in full or not at all.
+    fn try_spend(&mut self, cost: &Cost) -> Result<(), InsufficientResources> {
+        let missing = self.missing(cost);
+        if !missing.is_empty() {
+            return Err(InsufficientResources { missing });
+        }
+        for (kind, amount) in cost.iter() {
+            self.remove(kind, amount);
+        }
+        Ok(())
+    }
 }
 
 impl Display for GameResources {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Wood: {}, Stone: {}, Gold: {}",
-            self.wood, self.stone, self.gold
+            "Wood: {}, Stone: {}, Gold: {}, Food: {}, Population: {}",
+            self.0.wood, self.0.stone, self.0.gold, self.0.food, self.0.population
         )
     }
 }
+
+/// The only way to change [`GameResources`], writes a [`ResourcesChanged`] for every change.
+#[derive(SystemParam)]
+pub struct Treasury<'w> {
+    resources: ResMut<'w, GameResources>,
+    changes: MessageWriter<'w, ResourcesChanged>,
+}
+
+impl Treasury<'_> {
+    pub fn resources(&self) -> &GameResources {
+        &self.resources
+    }
+
+    pub fn add(&mut self, kind: ResourceKind, amount: u32) {
+        let added = self.resources.add(kind, amount);
+        if added > 0 {
+            self.changes.write(ResourcesChanged {
+                kind,
+                delta: added.into(),
+            });
+        }
+    }
+
+    pub fn try_spend(&mut self, cost: &Cost) -> Result<(), InsufficientResources> {
+        self.resources.try_spend(cost)?;
+        self.changes
+            .write_batch(cost.iter().map(|(kind, amount)| ResourcesChanged {
+                kind,
+                delta: -i64::from(amount),
+            }));
+        Ok(())
+    }
+
+    /// Replaces the whole stock, e.g. when loading a save.
+    pub fn set_stock(&mut self, stock: Cost) {
+        let previous = self.resources.stock();
+        self.resources.0 = stock;
+        self.changes
+            .write_batch(ResourceKind::ALL.into_iter().filter_map(|kind| {
+                let delta = i64::from(stock.get(kind)) - i64::from(previous.get(kind));
+                (delta != 0).then_some(ResourcesChanged { kind, delta })
+            }));
+    }
+}
+
+/// Every game starts with an empty stock, a loaded save restores its own afterwards.
+fn reset_game_resources(mut treasury: Treasury) {
+    treasury.set_stock(Cost::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn set_stock(In(stock): In<Cost>, mut treasury: Treasury) {
+        treasury.set_stock(stock);
+    }
+
+    #[test]
+    fn setting_the_stock_reports_every_change() {
+        let mut world = World::new();
+        world.init_resource::<GameResources>();
+        world.init_resource::<Messages<ResourcesChanged>>();
+
+        let first = Cost {
+            wood: 5,
+            gold: 2,
+            ..default()
+        };
+        let second = Cost {
+            wood: 3,
+            stone: 4,
+            gold: 2,
+            ..default()
+        };
+        world.run_system_once_with(set_stock, first).unwrap();
+        world.run_system_once_with(set_stock, second).unwrap();
+
+        let changes: Vec<_> = world
+            .resource::<Messages<ResourcesChanged>>()
+            .iter_current_update_messages()
+            .copied()
+            .collect();
+        let change = |kind, delta| ResourcesChanged { kind, delta };
+        assert_eq!(
+            changes,
+            [
+                change(ResourceKind::Wood, 5),
+                change(ResourceKind::Gold, 2),
+                change(ResourceKind::Wood, -2),
+                change(ResourceKind::Stone, 4),
+            ]
+        );
+        assert_eq!(world.resource::<GameResources>().stock(), second);
+    }
+}
@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use avian3d::prelude::ColliderDisabled;
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    effects::{
+        floating_text::FloatingText,
+        mesh_material_override::{MaterialOverride, MaterialOverrideCommands, MaterialOverrideId},
+        particles::{ParticleEffect, SpawnParticles},
+    },
+    game_resources::{ResourceKind, Treasury},
+    health::{DamageEvent, DamageKind, Dead, DespawnPolicy, Died, Health},
+    leaf_material::{LeafAssets, LeafMaterial},
+    player::PlayerHit,
+    research::{UnlockedFeatures, upgrades},
+    tool::HeldTool,
+};
+
+/// Time until a depleted node can be harvested again.
+pub const RESPAWN_DELAY: Duration = Duration::from_secs(90);
+
+/// The hit flash shows over longer lasting overrides, e.g. a highlight.
+const HIT_FLASH_PRIORITY: i32 = 10;
+
+/// Height above a node's origin at which the resources gained are shown.
+const YIELD_TEXT_HEIGHT: f32 = 1.5;
+
+/// Height above a hit on a tree from which its leaves fall.
+const LEAVES_HEIGHT: f32 = 2.0;
+
+pub struct HarvestablePlugin;
+
+impl Plugin for HarvestablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HarvestAssets>()
+            .add_observer(harvest)
+            .add_observer(hit_particles)
+            .add_observer(deplete)
+            .add_systems(Update, respawn_depleted.run_if(in_state(GameState::InGame)));
+    }
+}
+
+/// A resource node, like a tree or a rock, that yields resources whenever it is damaged.
+/// It is depleted once its [`Health`] runs out.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+#[require(DespawnPolicy = DespawnPolicy::LeaveCorpse)]
+pub struct Harvestable {
+    pub kind: ResourceKind,
+    pub yield_per_hit: u32,
+}
+
+impl Harvestable {
+    pub fn new(kind: ResourceKind, yield_per_hit: u32) -> Self {
+        Self {
+            kind,
+            yield_per_hit,
+        }
+    }
+}
+
+/// Stable index of a generated node, to find it again after loading a save.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HarvestableId(pub u32);
+
+/// A node that was harvested completely. It is hidden behind its remains until it respawns.
+#[derive(Component, Debug)]
+pub struct Depleted {
+    timer: Timer,
+    remains: Entity,
+}
+
+impl Depleted {
+    /// Time until the node respawns.
+    pub fn remaining(&self) -> Duration {
+        self.timer.remaining()
+    }
+
+    pub fn set_remaining(&mut self, remaining: Duration) {
+        let duration = self.timer.duration();
+        self.timer.set_elapsed(duration.saturating_sub(remaining));
+    }
+}
+
+/// Respawn delay of a node that is depleted again by [`restore_depleted`].
+#[derive(Component, Debug)]
+struct RestoredDepletion {
+    respawn_in: Duration,
+}
+
+/// Depletes a node without harvesting it, e.g. when it is restored from a save, so it
+/// respawns after `respawn_in`. Its [`Health`] should already be zero.
+pub fn restore_depleted(commands: &mut Commands, entity: Entity, respawn_in: Duration) {
+    commands
+        .entity(entity)
+        .insert((Dead::default(), RestoredDepletion { respawn_in }));
+    commands.trigger(Died {
+        entity,
+        source: entity,
+        kind: DamageKind::Blunt,
+    });
+}
+
+#[derive(Resource)]
+struct HarvestAssets {
+    /// Flashes a node's meshes when it is hit.
+    hit_flash_id: MaterialOverrideId,
+    hit_flash: Handle<StandardMaterial>,
+    stump: (Handle<Mesh>, Handle<StandardMaterial>),
+    rubble: (Handle<Mesh>, Handle<StandardMaterial>),
+}
+
+impl FromWorld for HarvestAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let stump_mesh = meshes.add(Cylinder::new(0.25, 0.3));
+        let rubble_mesh = meshes.add(Sphere::new(0.4).mesh().ico(0).unwrap());
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            hit_flash_id: MaterialOverrideId::unique(),
+            hit_flash: materials.add(StandardMaterial {
+                unlit: true,
+                base_color: Color::WHITE.darker(0.7),
+                ..default()
+            }),
+            stump: (
+                stump_mesh,
+                materials.add(StandardMaterial {
+                    base_color: Srgba::hex("6b4a2f").unwrap().into(),
+                    perceptual_roughness: 1.0,
+                    ..default()
+                }),
+            ),
+            rubble: (
+                rubble_mesh,
+                materials.add(StandardMaterial {
+                    base_color: Srgba::hex("7a7671").unwrap().into(),
+                    perceptual_roughness: 1.0,
+                    ..default()
+                }),
+            ),
+        }
+    }
+}
+
+fn harvest(
+    damage: On<DamageEvent>,
+    mut commands: Commands,
+    harvestables: Query<(&Harvestable, &GlobalTransform), Without<Dead>>,
+    tools: Query<&HeldTool>,
+    children: Query<&Children>,
+    leaves: Query<(), With<MeshMaterial3d<LeafMaterial>>>,
+    mut treasury: Treasury,
+    unlocked: UnlockedFeatures,
+    assets: Res<HarvestAssets>,
+    leaf_assets: Res<LeafAssets>,
+) {
+    // The damage propagates up from the collider to the node
+    let node = damage.event_target();
+    let Ok((harvestable, transform)) = harvestables.get(node) else {
+        return;
+    };
+
+    for entity in std::iter::once(node).chain(children.iter_descendants(node)) {
+        // Leaves keep their billboards while flashing
+        let flash = if leaves.contains(entity) {
+            MaterialOverride::new(assets.hit_flash_id, leaf_assets.hit_flash.clone())
+        } else {
+            MaterialOverride::new(assets.hit_flash_id, assets.hit_flash.clone())
+        };
+        commands.entity(entity).push_material_override(
+            flash
+                .with_priority(HIT_FLASH_PRIORITY)
+                .lasting(Duration::from_millis(50)),
+        );
+    }
+
+    let bonus = if unlocked.is_unlocked(upgrades::SHARP_TOOLS) {
+        1
+    } else {
+        0
+    };
+    // Damage from anything but a tool, e.g. an explosion, yields as usual
+    let multiplier = tools.get(damage.source).map_or(1, |held| {
+        held.tool.modifiers(Some(harvestable.kind)).yield_multiplier
+    });
+    if multiplier > 0 {
+        let amount = (harvestable.yield_per_hit + bonus) * multiplier;
+        treasury.add(harvestable.kind, amount);
+        commands.trigger(FloatingText::new(
+            format!("+{amount} {}", harvestable.kind),
+            transform.translation() + Vec3::Y * YIELD_TEXT_HEIGHT,
+            Color::WHITE,
+        ));
+    }
+}
+
+fn hit_particles(hit: On<PlayerHit>, mut commands: Commands, harvestables: Query<&Harvestable>) {
+    // The hit propagates up from the collider to the node
+    let Ok(harvestable) = harvestables.get(hit.event_target()) else {
+        return;
+    };
+    let effect = match harvestable.kind {
+        ResourceKind::Stone => ParticleEffect::StoneDust,
+        _ => ParticleEffect::WoodChips,
+    };
+    commands.trigger(SpawnParticles::new(effect, hit.point, hit.hit.normal));
+    if harvestable.kind == ResourceKind::Wood {
+        commands.trigger(SpawnParticles::new(
+            ParticleEffect::Leaves,
+            hit.point + Vec3::Y * LEAVES_HEIGHT,
+            Vec3::Y,
+        ));
+    }
+}
+
+/// Hides a harvested node behind its remains until it respawns.
+fn deplete(
+    died: On<Died>,
+    mut commands: Commands,
+    harvestables: Query<(
+        &Harvestable,
+        &Transform,
+        Option<&ChildOf>,
+        Option<&RestoredDepletion>,
+    )>,
+    assets: Res<HarvestAssets>,
+) {
+    let Ok((harvestable, transform, child_of, restored)) = harvestables.get(died.entity) else {
+        return;
+    };
+
+    let (mesh, material) = match harvestable.kind {
+        ResourceKind::Stone => assets.rubble.clone(),
+        _ => assets.stump.clone(),
+    };
+    let remains = commands
+        .spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            // Half buried, so stumps and rubble sit on the ground
+            Transform::from_translation(transform.translation + Vec3::Y * 0.15)
+                .with_rotation(transform.rotation),
+            DespawnOnExit(GameState::InGame),
+        ))
+        .id();
+    // The remains share the node's parent, so they are despawned together, e.g. with its chunk
+    if let Some(child_of) = child_of {
+        commands.entity(remains).insert(ChildOf(child_of.parent()));
+    }
+
+    let mut depleted = Depleted {
+        timer: Timer::new(RESPAWN_DELAY, TimerMode::Once),
+        remains,
+    };
+    if let Some(restored) = restored {
+        depleted.set_remaining(restored.respawn_in);
+    }
+    commands
+        .entity(died.entity)
+        .insert((depleted, Visibility::Hidden))
+        .remove::<RestoredDepletion>()
+        .insert_recursive::<Children>(ColliderDisabled);
+}
+
+fn respawn_depleted(
+    mut commands: Commands,
+    mut depleted: Query<(Entity, &mut Depleted, &mut Health)>,
+    time: Res<Time>,
+) {
+    for (entity, mut node, mut health) in depleted.iter_mut() {
+        node.timer.tick(time.delta());
+        if !node.timer.is_finished() {
+            continue;
+        }
+        health.refill();
+        commands.entity(node.remains).despawn();
+        commands
+            .entity(entity)
+            .remove::<(Depleted, Dead)>()
+            .insert(Visibility::Inherited)
+            .remove_recursive::<Children, ColliderDisabled>();
+    }
+}
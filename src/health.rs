@@ -0,0 +1,178 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(apply_damage)
+            .add_observer(apply_despawn_policy)
+            .add_systems(Update, despawn_corpses);
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+#[require(DespawnPolicy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    /// Restores full health, e.g. when a resource node respawns.
+    pub fn refill(&mut self) {
+        self.current = self.max;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum DamageKind {
+    /// Hammers and clubs.
+    Blunt,
+    /// Arrows, other projectiles and pickaxes.
+    Pierce,
+    /// Blades, e.g. axes and enemy swords.
+    Slash,
+    Fire,
+}
+
+/// Damages the first entity with [`Health`], starting at `entity` and propagating up to its
+/// parents. This allows hitting any collider of an entity.
+#[derive(EntityEvent, Debug, Clone, Copy, PartialEq)]
+#[entity_event(propagate)]
+#[entity_event(auto_propagate)]
+pub struct DamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+    /// Entity that dealt the damage, e.g. a tower or the player.
+    pub source: Entity,
+    pub kind: DamageKind,
+}
+
+/// Triggered once [`Health`] drops to zero.
+#[derive(EntityEvent, Debug, Clone, Copy, PartialEq)]
+pub struct Died {
+    pub entity: Entity,
+    /// Entity that dealt the final blow.
+    pub source: Entity,
+    pub kind: DamageKind,
+}
+
+/// Flat reduction of every hit, applied after [`Resistances`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Armor(pub f32);
+
+/// Damage multipliers by [`DamageKind`], missing kinds deal full damage and zero is immune.
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct Resistances(pub HashMap<DamageKind, f32>);
+
+impl Resistances {
+    pub fn with(mut self, kind: DamageKind, multiplier: f32) -> Self {
+        self.0.insert(kind, multiplier);
+        self
+    }
+
+    pub fn multiplier(&self, kind: DamageKind) -> f32 {
+        self.0.get(&kind).copied().unwrap_or(1.0)
+    }
+}
+
+/// Damage that is actually dealt after resistances and armor, never negative.
+pub fn mitigate(
+    amount: f32,
+    kind: DamageKind,
+    armor: Option<&Armor>,
+    resistances: Option<&Resistances>,
+) -> f32 {
+    let resisted = amount * resistances.map_or(1.0, |resistances| resistances.multiplier(kind));
+    (resisted - armor.map_or(0.0, |armor| armor.0)).max(0.0)
+}
+
+/// What happens to an entity once it [`Died`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DespawnPolicy {
+    #[default]
+    Immediate,
+    /// Gives a death animation time to play.
+    AfterDelay(Duration),
+    /// Stays in the world, e.g. to be revived or to respawn.
+    LeaveCorpse,
+}
+
+/// Marks entities that died. Their [`Health`] is zero, so they ignore any further damage.
+#[derive(Component, Debug, Default)]
+pub struct Dead {
+    despawn_timer: Option<Timer>,
+}
+
+fn apply_damage(
+    mut damage: On<DamageEvent>,
+    mut commands: Commands,
+    mut targets: Query<(&mut Health, Option<&Armor>, Option<&Resistances>)>,
+) {
+    let entity = damage.entity;
+    let Ok((mut health, armor, resistances)) = targets.get_mut(entity) else {
+        return;
+    };
+    damage.propagate(false);
+    // Already died, e.g. from an earlier hit this frame
+    if health.is_dead() {
+        return;
+    }
+
+    let amount = mitigate(damage.amount, damage.kind, armor, resistances);
+    health.current = (health.current - amount).max(0.0);
+    if !health.is_dead() {
+        return;
+    }
+    commands.entity(entity).insert(Dead::default());
+    commands.trigger(Died {
+        entity,
+        source: damage.source,
+        kind: damage.kind,
+    });
+}
+
+fn apply_despawn_policy(
+    died: On<Died>,
+    mut commands: Commands,
+    mut dead: Query<(&DespawnPolicy, &mut Dead)>,
+) {
+    let Ok((policy, mut dead)) = dead.get_mut(died.entity) else {
+        return;
+    };
+    match policy {
+        DespawnPolicy::Immediate => {
+            commands.entity(died.entity).try_despawn();
+        }
+        DespawnPolicy::AfterDelay(delay) => {
+            dead.despawn_timer = Some(Timer::new(*delay, TimerMode::Once));
+        }
+        DespawnPolicy::LeaveCorpse => {}
+    }
+}
+
+fn despawn_corpses(mut commands: Commands, mut dead: Query<(Entity, &mut Dead)>, time: Res<Time>) {
+    for (entity, mut dead) in dead.iter_mut() {
+        let Some(timer) = &mut dead.despawn_timer else {
+            continue;
+        };
+        timer.tick(time.delta());
+        if timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+};
+
+use bevy::{input::InputSystems, prelude::*};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::settings::Settings;
+
+/// Number of [`Action::SelectBuilding`] slots.
+pub const BUILDING_SLOTS: u8 = 9;
+
+pub struct InputActionsPlugin;
+
+impl Plugin for InputActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputActions>()
+            .add_systems(PreUpdate, update_input_actions.after(InputSystems));
+    }
+}
+
+/// Something the player can do, bound to keys and buttons by [`InputBindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Sprint,
+    /// Swings the held tool at whatever is in reach, e.g. to chop trees.
+    Hit,
+    /// Switches to the next tool.
+    NextTool,
+    /// Interacts with the targeted point, e.g. to place buildings.
+    Interact,
+    /// Opens the pause menu, or resumes the game.
+    Pause,
+    OpenBuildMenu,
+    RotateBuilding,
+    /// Selects the building at this index in the catalog.
+    SelectBuilding(u8),
+    OpenResearch,
+}
+
+impl Action {
+    /// Every action, in the order they are listed for rebinding.
+    pub fn all() -> impl Iterator<Item = Action> {
+        [
+            Action::MoveForward,
+            Action::MoveBack,
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::Jump,
+            Action::Sprint,
+            Action::Hit,
+            Action::NextTool,
+            Action::Interact,
+            Action::Pause,
+            Action::OpenBuildMenu,
+            Action::RotateBuilding,
+            Action::OpenResearch,
+        ]
+        .into_iter()
+        .chain((0..BUILDING_SLOTS).map(Action::SelectBuilding))
+    }
+
+    pub fn label(self) -> String {
+        match self {
+            Action::MoveForward => "Move Forward".to_owned(),
+            Action::MoveBack => "Move Back".to_owned(),
+            Action::MoveLeft => "Move Left".to_owned(),
+            Action::MoveRight => "Move Right".to_owned(),
+            Action::Jump => "Jump".to_owned(),
+            Action::Sprint => "Sprint".to_owned(),
+            Action::Hit => "Hit".to_owned(),
+            Action::NextTool => "Next Tool".to_owned(),
+            Action::Interact => "Interact".to_owned(),
+            Action::Pause => "Pause".to_owned(),
+            Action::OpenBuildMenu => "Build Mode".to_owned(),
+            Action::RotateBuilding => "Rotate Building".to_owned(),
+            Action::SelectBuilding(slot) => format!("Building {}", slot + 1),
+            Action::OpenResearch => "Research".to_owned(),
+        }
+    }
+}
+
+/// A key or button an [`Action`] is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button of any connected gamepad. Triggers count as pressed past their threshold.
+    Gamepad(GamepadButton),
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                write!(f, "{name}")
+            }
+            Binding::Mouse(button) => write!(f, "{button:?} Mouse"),
+            Binding::Gamepad(button) => write!(f, "Pad {button:?}"),
+        }
+    }
+}
+
+/// Bindings of every [`Action`], persisted with the [`Settings`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct InputBindings(BTreeMap<Action, Vec<Binding>>);
+
+impl<'de> Deserialize<'de> for InputBindings {
+    /// Actions missing from the stored bindings, e.g. added by an update, get their default
+    /// bindings unless those are taken by another action.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut bindings = BTreeMap::<Action, Vec<Binding>>::deserialize(deserializer)?;
+        let bound: HashSet<Binding> = bindings.values().flatten().copied().collect();
+        for (action, mut defaults) in InputBindings::default().0 {
+            if !bindings.contains_key(&action) {
+                defaults.retain(|binding| !bound.contains(binding));
+                bindings.insert(action, defaults);
+            }
+        }
+        Ok(Self(bindings))
+    }
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key, Mouse};
+
+        let mut bindings = BTreeMap::from([
+            (
+                Action::MoveForward,
+                vec![Key(KeyCode::KeyW), Key(KeyCode::ArrowUp)],
+            ),
+            (
+                Action::MoveBack,
+                vec![Key(KeyCode::KeyS), Key(KeyCode::ArrowDown)],
+            ),
+            (
+                Action::MoveLeft,
+                vec![Key(KeyCode::KeyA), Key(KeyCode::ArrowLeft)],
+            ),
+            (
+                Action::MoveRight,
+                vec![Key(KeyCode::KeyD), Key(KeyCode::ArrowRight)],
+            ),
+            (
+                Action::Jump,
+                vec![Key(KeyCode::Space), Gamepad(GamepadButton::South)],
+            ),
+            (
+                Action::Sprint,
+                vec![Key(KeyCode::ShiftLeft), Gamepad(GamepadButton::LeftThumb)],
+            ),
+            (
+                Action::Hit,
+                vec![
+                    Mouse(MouseButton::Left),
+                    Gamepad(GamepadButton::RightTrigger2),
+                ],
+            ),
+            (
+                Action::NextTool,
+                vec![Key(KeyCode::KeyQ), Gamepad(GamepadButton::RightTrigger)],
+            ),
+            (
+                Action::Interact,
+                vec![
+                    Mouse(MouseButton::Right),
+                    Gamepad(GamepadButton::LeftTrigger2),
+                ],
+            ),
+            (
+                Action::Pause,
+                vec![Key(KeyCode::Escape), Gamepad(GamepadButton::Start)],
+            ),
+            (
+                Action::OpenBuildMenu,
+                vec![Key(KeyCode::KeyB), Gamepad(GamepadButton::North)],
+            ),
+            (
+                Action::RotateBuilding,
+                vec![Key(KeyCode::KeyR), Gamepad(GamepadButton::West)],
+            ),
+            (
+                Action::OpenResearch,
+                vec![Key(KeyCode::KeyT), Gamepad(GamepadButton::Select)],
+            ),
+        ]);
+        let digits = [
+            KeyCode::Digit1,
+            KeyCode::Digit2,
+            KeyCode::Digit3,
+            KeyCode::Digit4,
+            KeyCode::Digit5,
+            KeyCode::Digit6,
+            KeyCode::Digit7,
+            KeyCode::Digit8,
+            KeyCode::Digit9,
+        ];
+        for (slot, digit) in (0..BUILDING_SLOTS).zip(digits) {
+            bindings.insert(Action::SelectBuilding(slot), vec![Key(digit)]);
+        }
+        Self(bindings)
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Binds `action` to `binding` alone, unbinding it from any other action.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        for bindings in self.0.values_mut() {
+            bindings.retain(|bound| *bound != binding);
+        }
+        self.0.insert(action, vec![binding]);
+    }
+}
+
+/// State of every [`Action`] this frame. Gameplay reads this instead of the keys.
+#[derive(Resource, Debug, Default)]
+pub struct InputActions {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    just_released: HashSet<Action>,
+    movement: Vec2,
+    look: Vec2,
+}
+
+impl InputActions {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// `1.0` while only `positive` is pressed, `-1.0` while only `negative` is.
+    pub fn axis(&self, negative: Action, positive: Action) -> f32 {
+        self.pressed(positive) as i8 as f32 - self.pressed(negative) as i8 as f32
+    }
+
+    /// Walking direction from the move actions and the left stick, `y` is forward.
+    /// At most one long.
+    pub fn movement(&self) -> Vec2 {
+        self.movement
+    }
+
+    /// Right stick deflection, `y` is up. Mouse motion is read directly as it isn't a rate.
+    pub fn look(&self) -> Vec2 {
+        self.look
+    }
+}
+
+/// Applies a radial `deadzone` to a stick and rescales the rest to the full range, raised to
+/// `exponent` for finer control of small deflections.
+pub fn shape_stick(raw: Vec2, deadzone: f32, exponent: f32) -> Vec2 {
+    let length = raw.length();
+    if length <= deadzone || deadzone >= 1.0 {
+        return Vec2::ZERO;
+    }
+    let scaled = ((length.min(1.0) - deadzone) / (1.0 - deadzone)).powf(exponent);
+    raw / length * scaled
+}
+
+fn update_input_actions(
+    mut actions: ResMut<InputActions>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    settings: Res<Settings>,
+) {
+    let pressed: HashSet<Action> = Action::all()
+        .filter(|action| {
+            settings
+                .bindings
+                .get(*action)
+                .iter()
+                .any(|binding| match binding {
+                    Binding::Key(key) => keys.pressed(*key),
+                    Binding::Mouse(button) => mouse_buttons.pressed(*button),
+                    Binding::Gamepad(button) => {
+                        gamepads.iter().any(|gamepad| gamepad.pressed(*button))
+                    }
+                })
+        })
+        .collect();
+
+    let actions = &mut *actions;
+    actions.just_pressed = pressed.difference(&actions.pressed).copied().collect();
+    actions.just_released = actions.pressed.difference(&pressed).copied().collect();
+    actions.pressed = pressed;
+
+    let shape = |stick: Vec2| shape_stick(stick, settings.stick_deadzone, settings.stick_curve);
+    let left_stick: Vec2 = gamepads
+        .iter()
+        .map(|gamepad| shape(gamepad.left_stick()))
+        .sum();
+    let right_stick: Vec2 = gamepads
+        .iter()
+        .map(|gamepad| shape(gamepad.right_stick()))
+        .sum();
+    let keys = Vec2::new(
+        actions.axis(Action::MoveLeft, Action::MoveRight),
+        actions.axis(Action::MoveBack, Action::MoveForward),
+    );
+    actions.movement = (keys + left_stick).clamp_length_max(1.0);
+    actions.look = right_stick.clamp_length_max(1.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::{
+        InputPlugin,
+        gamepad::{
+            GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+            RawGamepadButtonChangedEvent,
+        },
+    };
+
+    use bevy::{
+        input_focus::{InputFocus, InputFocusVisible},
+        ui::InteractionDisabled,
+        ui_widgets::{Activate, Button},
+    };
+
+    use super::*;
+    use crate::{testing::headless_app, ui::gamepad_navigation::gamepad_navigation};
+
+    /// An app with a single connected gamepad.
+    fn gamepad_app() -> (App, Entity) {
+        let mut app = headless_app();
+        app.add_plugins((InputPlugin, InputActionsPlugin))
+            .init_resource::<Settings>();
+        let gamepad = app.world_mut().spawn_empty().id();
+        app.world_mut().write_message(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected {
+                name: "Test Pad".to_owned(),
+                vendor_id: None,
+                product_id: None,
+            },
+        ));
+        app.update();
+        (app, gamepad)
+    }
+
+    fn tilt(app: &mut App, gamepad: Entity, axis: GamepadAxis, value: f32) {
+        app.world_mut()
+            .write_message(RawGamepadAxisChangedEvent::new(gamepad, axis, value));
+        app.update();
+    }
+
+    fn press(app: &mut App, gamepad: Entity, button: GamepadButton) {
+        app.world_mut()
+            .write_message(RawGamepadButtonChangedEvent::new(gamepad, button, 1.0));
+        app.update();
+        app.world_mut()
+            .write_message(RawGamepadButtonChangedEvent::new(gamepad, button, 0.0));
+        app.update();
+    }
+
+    fn actions(app: &App) -> &InputActions {
+        app.world().resource::<InputActions>()
+    }
+
+    #[test]
+    fn missing_actions_get_their_default_bindings() {
+        let bindings: InputBindings =
+            ron::from_str("{ MoveForward: [Key(KeyI)], Jump: [Key(KeyQ)] }").unwrap();
+        assert_eq!(
+            bindings.get(Action::MoveForward),
+            [Binding::Key(KeyCode::KeyI)]
+        );
+        assert_eq!(
+            bindings.get(Action::OpenResearch),
+            InputBindings::default().get(Action::OpenResearch)
+        );
+        // Q is taken by the jump, so only the gamepad binding is added
+        assert_eq!(
+            bindings.get(Action::NextTool),
+            [Binding::Gamepad(GamepadButton::RightTrigger)]
+        );
+    }
+
+    #[test]
+    fn small_stick_deflections_are_ignored() {
+        let (mut app, gamepad) = gamepad_app();
+        tilt(&mut app, gamepad, GamepadAxis::LeftStickX, 0.1);
+        assert_eq!(actions(&app).movement(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn left_stick_moves() {
+        let (mut app, gamepad) = gamepad_app();
+        tilt(&mut app, gamepad, GamepadAxis::LeftStickY, 1.0);
+        assert!(actions(&app).movement().distance(Vec2::Y) < 1e-4);
+
+        tilt(&mut app, gamepad, GamepadAxis::LeftStickY, 0.0);
+        assert_eq!(actions(&app).movement(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn right_trigger_hits() {
+        let (mut app, gamepad) = gamepad_app();
+        app.world_mut()
+            .write_message(RawGamepadButtonChangedEvent::new(
+                gamepad,
+                GamepadButton::RightTrigger2,
+                1.0,
+            ));
+        app.update();
+        assert!(actions(&app).just_pressed(Action::Hit));
+        assert!(actions(&app).pressed(Action::Hit));
+
+        app.update();
+        assert!(actions(&app).pressed(Action::Hit));
+        assert!(!actions(&app).just_pressed(Action::Hit));
+    }
+
+    #[derive(Resource, Default)]
+    struct Activated(Vec<Entity>);
+
+    #[test]
+    fn disabled_buttons_are_not_activated() {
+        let (mut app, gamepad) = gamepad_app();
+        app.init_resource::<InputFocus>()
+            .init_resource::<InputFocusVisible>()
+            .init_resource::<Activated>()
+            .add_systems(Update, gamepad_navigation)
+            .add_observer(|activate: On<Activate>, mut activated: ResMut<Activated>| {
+                activated.0.push(activate.entity);
+            });
+        let disabled = app.world_mut().spawn((Button, InteractionDisabled)).id();
+        let enabled = app.world_mut().spawn(Button).id();
+
+        app.world_mut().resource_mut::<InputFocus>().set(disabled);
+        press(&mut app, gamepad, GamepadButton::South);
+        assert!(app.world().resource::<Activated>().0.is_empty());
+
+        app.world_mut().resource_mut::<InputFocus>().set(enabled);
+        press(&mut app, gamepad, GamepadButton::South);
+        assert_eq!(app.world().resource::<Activated>().0, [enabled]);
+    }
+}
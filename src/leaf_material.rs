@@ -1,11 +1,93 @@
+use std::f32::consts::TAU;
+
 use bevy::{
-    pbr::MaterialExtension, prelude::*, render::render_resource::AsBindGroup, shader::ShaderRef,
+    mesh::MeshVertexBufferLayoutRef,
+    pbr::{ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline},
+    prelude::*,
+    render::render_resource::{
+        AsBindGroup, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+    },
+    shader::ShaderRef,
 };
 const SHADER_ASSET_PATH: &str = "shaders/leaf_material_extension.wgsl";
 const VERTEX_SHADER_ASSET_PATH: &str = "shaders/leaf_material_vert.wgsl";
 
+/// Flutter of the single leaves in radians per second.
+const FLUTTER_SPEED: f32 = 7.0;
+
+const LEAF_TEXTURE_PATH: &str = "textures/leaf.png";
+
+pub type LeafMaterial = ExtendedMaterial<StandardMaterial, LeafMaterialExtension>;
+
+/// Renders leaves as billboards swaying in the [`Wind`]. Every tree shares the material of the
+/// [`LeafAssets`], so they all sway together.
+pub struct LeafMaterialPlugin;
+
+impl Plugin for LeafMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<LeafMaterial>::default())
+            .init_resource::<Wind>()
+            .init_resource::<LeafAssets>()
+            .add_systems(Update, blow_wind);
+    }
+}
+
+/// Wind blowing through the trees. Gusts roll across the world along its direction.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Wind {
+    /// Direction the wind blows towards on the XZ plane.
+    pub direction: Vec2,
+    /// Distance the leaves are pushed in a full gust in world units.
+    pub strength: f32,
+    /// Gusts per second.
+    pub gust_frequency: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self {
+            direction: Vec2::new(1.0, 0.3),
+            strength: 0.15,
+            gust_frequency: 0.2,
+        }
+    }
+}
+
 #[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
-pub struct LeafMaterialExtension {}
+pub struct LeafMaterialExtension {
+    #[uniform(100)]
+    pub leaves: LeafUniform,
+}
+
+#[derive(ShaderType, Reflect, Debug, Clone)]
+pub struct LeafUniform {
+    /// Normalized direction of the [`Wind`].
+    pub wind_direction: Vec2,
+    pub wind_strength: f32,
+    /// Angles in `0..TAU` the gusts and the flutter of the leaves are at. Advanced on the CPU
+    /// and wrapped there, so the animation never jumps.
+    pub gust_phase: f32,
+    pub flutter_phase: f32,
+    /// Range of the billboards' half size, picked per billboard.
+    pub min_leaf_size: f32,
+    pub max_leaf_size: f32,
+    /// How much the shade of the leaves differs between trees, from 0 to 1.
+    pub tint_variation: f32,
+}
+
+impl Default for LeafUniform {
+    fn default() -> Self {
+        Self {
+            wind_direction: Vec2::X,
+            wind_strength: 0.0,
+            gust_phase: 0.0,
+            flutter_phase: 0.0,
+            min_leaf_size: 0.2,
+            max_leaf_size: 0.7,
+            tint_variation: 0.3,
+        }
+    }
+}
 
 impl MaterialExtension for LeafMaterialExtension {
     fn fragment_shader() -> ShaderRef {
@@ -20,4 +102,89 @@ impl MaterialExtension for LeafMaterialExtension {
     fn deferred_vertex_shader() -> ShaderRef {
         VERTEX_SHADER_ASSET_PATH.into()
     }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // The fragment shader tints each tree by its transform
+        descriptor
+            .vertex
+            .shader_defs
+            .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+        if let Some(fragment) = &mut descriptor.fragment {
+            fragment
+                .shader_defs
+                .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+        }
+        Ok(())
+    }
+}
+
+/// The leaf material shared by every tree.
+#[derive(Resource)]
+pub struct LeafAssets {
+    pub material: Handle<LeafMaterial>,
+    /// Shown over the leaves of a tree when it is hit. Sways with the [`LeafAssets::material`].
+    pub hit_flash: Handle<LeafMaterial>,
+}
+
+impl FromWorld for LeafAssets {
+    fn from_world(world: &mut World) -> Self {
+        let texture = world.resource::<AssetServer>().load(LEAF_TEXTURE_PATH);
+        let material = ExtendedMaterial {
+            base: StandardMaterial {
+                base_color_texture: Some(texture),
+                cull_mode: None,
+                double_sided: true,
+                alpha_mode: AlphaMode::Mask(0.1),
+                ..default()
+            },
+            extension: LeafMaterialExtension::default(),
+        };
+        let hit_flash = ExtendedMaterial {
+            base: StandardMaterial {
+                unlit: true,
+                ..material.base.clone()
+            },
+            ..material.clone()
+        };
+
+        let mut materials = world.resource_mut::<Assets<LeafMaterial>>();
+        Self {
+            material: materials.add(material),
+            hit_flash: materials.add(hit_flash),
+        }
+    }
+}
+
+/// Angles of the gusts and the flutter, see [`LeafUniform::gust_phase`].
+#[derive(Debug, Default)]
+struct WindPhase {
+    gust: f32,
+    flutter: f32,
+}
+
+fn blow_wind(
+    mut materials: ResMut<Assets<LeafMaterial>>,
+    mut phase: Local<WindPhase>,
+    leaf_assets: Res<LeafAssets>,
+    wind: Res<Wind>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    phase.gust = (phase.gust + delta * wind.gust_frequency * TAU).rem_euclid(TAU);
+    phase.flutter = (phase.flutter + delta * FLUTTER_SPEED).rem_euclid(TAU);
+    for handle in [&leaf_assets.material, &leaf_assets.hit_flash] {
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        let leaves = &mut material.extension.leaves;
+        leaves.wind_direction = wind.direction.normalize_or(Vec2::X);
+        leaves.wind_strength = wind.strength;
+        leaves.gust_phase = phase.gust;
+        leaves.flutter_phase = phase.flutter;
+    }
 }
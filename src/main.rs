@@ -1,9 +1,27 @@
+pub mod audio;
+pub mod building;
 pub mod constants;
+pub mod day_night;
 pub mod effects;
+pub mod enemy;
 pub mod game_resources;
+pub mod harvestable;
+pub mod health;
+pub mod input;
 pub mod leaf_material;
+pub mod navigation;
+pub mod pause;
 mod player;
+pub mod research;
+pub mod save;
+pub mod settings;
+pub mod sky;
+#[cfg(test)]
+mod testing;
+pub mod tool;
+pub mod tower;
 pub mod ui;
+pub mod wave;
 mod world;
 
 use avian3d::PhysicsPlugins;
@@ -12,15 +30,38 @@ use bevy::{
     feathers::FeathersPlugin,
     image::ImageSamplerDescriptor,
     input_focus::{InputDispatchPlugin, tab_navigation::TabNavigationPlugin},
-    pbr::{ExtendedMaterial, wireframe::WireframeConfig},
+    pbr::wireframe::WireframeConfig,
     prelude::*,
     ui_widgets::UiWidgetsPlugins,
 };
 use puppeteer::PuppeteerPlugin;
 
 use crate::{
-    effects::delay_component::DelayComponentPlugin, game_resources::GameResources,
-    leaf_material::LeafMaterialExtension, player::PlayerPlugin, ui::UiPlugin, world::WorldPlugin,
+    audio::GameAudioPlugin,
+    building::BuildingPlugin,
+    day_night::DayNightPlugin,
+    effects::{
+        delay_component::DelayComponentPlugin, floating_text::FloatingTextPlugin,
+        mesh_material_override::MaterialOverridePlugin, particles::ParticlesPlugin,
+    },
+    enemy::EnemyPlugin,
+    game_resources::GameResourcesPlugin,
+    harvestable::HarvestablePlugin,
+    health::HealthPlugin,
+    input::InputActionsPlugin,
+    leaf_material::LeafMaterialPlugin,
+    navigation::NavigationPlugin,
+    pause::PausePlugin,
+    player::PlayerPlugin,
+    research::ResearchPlugin,
+    save::SavePlugin,
+    settings::SettingsPlugin,
+    sky::SkyPlugin,
+    tool::ToolPlugin,
+    tower::TowerPlugin,
+    ui::UiPlugin,
+    wave::WavePlugin,
+    world::{WorldPlugin, WorldSeed},
 };
 
 fn main() -> AppExit {
@@ -39,18 +80,40 @@ fn main() -> AppExit {
             }),
     )
     .init_state::<GameState>()
-    .insert_resource(UiScale(4.0))
+    .add_sub_state::<PauseState>()
     // Third party plugins
     .add_plugins((PhysicsPlugins::default(), PuppeteerPlugin))
     // Game plugins
     .add_plugins((
         DelayComponentPlugin,
+        GameResourcesPlugin,
         UiPlugin,
         PlayerPlugin,
         WorldPlugin,
-        MaterialPlugin::<ExtendedMaterial<StandardMaterial, LeafMaterialExtension>>::default(),
+        // Before the harvestables, which flash leaves with their own material
+        LeafMaterialPlugin,
+        HarvestablePlugin,
+        BuildingPlugin,
+        DayNightPlugin,
+        NavigationPlugin,
+        EnemyPlugin,
+        WavePlugin,
+        ResearchPlugin,
+        HealthPlugin,
+        TowerPlugin,
+    ))
+    .add_plugins((
+        SavePlugin,
+        SettingsPlugin,
+        InputActionsPlugin,
+        PausePlugin,
+        SkyPlugin,
+        GameAudioPlugin,
+        ToolPlugin,
+        ParticlesPlugin,
+        FloatingTextPlugin,
+        MaterialOverridePlugin,
     ))
-    .init_resource::<GameResources>()
     // Bevy plugins
     .add_plugins((
         // WireframePlugin::new(RenderDebugFlags::default()),
@@ -63,6 +126,11 @@ fn main() -> AppExit {
         global: true,
         ..default()
     });
+    // `--seed <seed>` starts a new game in that world right away
+    if let Some(seed) = std::env::args().skip_while(|arg| arg != "--seed").nth(1) {
+        app.insert_resource(WorldSeed::from_text(&seed))
+            .insert_state(GameState::InGame);
+    }
     app.run()
 }
 
@@ -70,5 +138,16 @@ fn main() -> AppExit {
 pub enum GameState {
     #[default]
     MainMenu,
+    NewGame,
+    Settings,
     InGame,
 }
+
+/// Whether the running game is paused, only exists while [`GameState::InGame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SubStates, Default)]
+#[source(GameState = GameState::InGame)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
@@ -0,0 +1,384 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    f32::consts::SQRT_2,
+};
+
+use avian3d::prelude::{ColliderAabb, ColliderOf, RigidBody};
+use bevy::prelude::*;
+
+use crate::world::{Heightmap, TERRAIN_SIZE, Terrain};
+
+/// Side length of a single navigation cell in world units.
+pub const CELL_SIZE: f32 = 1.0;
+
+/// Colliders that end below this height above the ground can be walked over.
+const STEP_HEIGHT: f32 = 0.3;
+
+/// Colliders that start this high above the ground can be walked under, e.g. tree canopies.
+const CLEARANCE: f32 = 2.0;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NavGrid::new(Vec2::splat(TERRAIN_SIZE), CELL_SIZE))
+            .add_observer(remove_obstacle)
+            .add_systems(PostUpdate, bake_obstacles);
+    }
+}
+
+/// Cells blocked by a static collider, stored so they can be released again.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NavFootprint {
+    pub min: UVec2,
+    pub max: UVec2,
+}
+
+/// Walkability grid covering the terrain, centered on the world origin.
+#[derive(Resource, Debug, Clone)]
+pub struct NavGrid {
+    size: UVec2,
+    cell_size: f32,
+    origin: Vec2,
+    /// Number of obstacles overlapping each cell.
+    blockers: Vec<u16>,
+    revision: u32,
+}
+
+impl NavGrid {
+    pub fn new(extent: Vec2, cell_size: f32) -> Self {
+        let size = (extent / cell_size).ceil().as_uvec2().max(UVec2::ONE);
+        Self {
+            size,
+            cell_size,
+            origin: -extent / 2.0,
+            blockers: vec![0; (size.x * size.y) as usize],
+            revision: 0,
+        }
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Incremented whenever the walkability of any cell changes.
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    /// Cell containing the world position, projected onto the XZ plane.
+    pub fn cell_at(&self, position: Vec2) -> Option<UVec2> {
+        let cell = ((position - self.origin) / self.cell_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let cell = cell.as_uvec2();
+        (cell.x < self.size.x && cell.y < self.size.y).then_some(cell)
+    }
+
+    pub fn cell_center(&self, cell: UVec2) -> Vec2 {
+        self.origin + (cell.as_vec2() + 0.5) * self.cell_size
+    }
+
+    /// Cells overlapped by the rectangle, clamped to the grid.
+    pub fn footprint(&self, min: Vec2, max: Vec2) -> Option<NavFootprint> {
+        let to_cell = |position: Vec2| ((position - self.origin) / self.cell_size).floor();
+        let min = to_cell(min).max(Vec2::ZERO);
+        let max = to_cell(max).min((self.size - 1).as_vec2());
+        (min.x <= max.x && min.y <= max.y).then(|| NavFootprint {
+            min: min.as_uvec2(),
+            max: max.as_uvec2(),
+        })
+    }
+
+    pub fn is_walkable(&self, cell: UVec2) -> bool {
+        self.index(cell)
+            .is_some_and(|index| self.blockers[index] == 0)
+    }
+
+    pub fn block(&mut self, footprint: NavFootprint) {
+        self.update_footprint(footprint, |blockers| blockers.saturating_add(1));
+    }
+
+    pub fn unblock(&mut self, footprint: NavFootprint) {
+        self.update_footprint(footprint, |blockers| blockers.saturating_sub(1));
+    }
+
+    /// Shortest path between two world positions on the XZ plane, including
+    /// the goal but excluding the start. Diagonal moves may not cut corners.
+    ///
+    /// If the goal is blocked, e.g. by a building, the path leads to the
+    /// closest walkable cell and then straight to the goal.
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let start_cell = self.cell_at(start)?;
+        let target_cell = self.cell_at(goal)?;
+        let goal_cell = self.nearest_walkable(target_cell)?;
+
+        let mut open = BinaryHeap::new();
+        let mut came_from = HashMap::<UVec2, UVec2>::new();
+        let mut cost = HashMap::<UVec2, f32>::new();
+        open.push(OpenCell {
+            cell: start_cell,
+            estimate: self.heuristic(start_cell, goal_cell),
+        });
+        cost.insert(start_cell, 0.0);
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            if cell == goal_cell {
+                let mut path = vec![goal];
+                if goal_cell != target_cell && goal_cell != start_cell {
+                    path.push(self.cell_center(goal_cell));
+                }
+                let mut current = cell;
+                while let Some(&previous) = came_from.get(&current) {
+                    if previous != start_cell {
+                        path.push(self.cell_center(previous));
+                    }
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let current_cost = cost[&cell];
+            for (neighbour, step) in self.neighbours(cell) {
+                let neighbour_cost = current_cost + step;
+                if cost
+                    .get(&neighbour)
+                    .is_some_and(|&known| known <= neighbour_cost)
+                {
+                    continue;
+                }
+                cost.insert(neighbour, neighbour_cost);
+                came_from.insert(neighbour, cell);
+                open.push(OpenCell {
+                    cell: neighbour,
+                    estimate: neighbour_cost + self.heuristic(neighbour, goal_cell),
+                });
+            }
+        }
+        None
+    }
+
+    fn index(&self, cell: UVec2) -> Option<usize> {
+        (cell.x < self.size.x && cell.y < self.size.y)
+            .then(|| (cell.y * self.size.x + cell.x) as usize)
+    }
+
+    fn update_footprint(&mut self, footprint: NavFootprint, update: impl Fn(u16) -> u16) {
+        for y in footprint.min.y..=footprint.max.y {
+            for x in footprint.min.x..=footprint.max.x {
+                let Some(index) = self.index(UVec2::new(x, y)) else {
+                    continue;
+                };
+                let blockers = update(self.blockers[index]);
+                if (blockers == 0) != (self.blockers[index] == 0) {
+                    self.revision = self.revision.wrapping_add(1);
+                }
+                self.blockers[index] = blockers;
+            }
+        }
+    }
+
+    /// Closest walkable cell by searching rings of growing size around `cell`.
+    pub fn nearest_walkable(&self, cell: UVec2) -> Option<UVec2> {
+        let center = cell.as_ivec2();
+        (0..self.size.max_element() as i32).find_map(|radius| {
+            (-radius..=radius)
+                .flat_map(|y| (-radius..=radius).map(move |x| IVec2::new(x, y)))
+                .filter(|offset| offset.x.abs() == radius || offset.y.abs() == radius)
+                .map(|offset| center + offset)
+                .filter(|cell| cell.x >= 0 && cell.y >= 0)
+                .map(|cell| cell.as_uvec2())
+                .filter(|cell| self.is_walkable(*cell))
+                .min_by_key(|candidate| candidate.as_ivec2().distance_squared(center))
+        })
+    }
+
+    fn neighbours(&self, cell: UVec2) -> impl Iterator<Item = (UVec2, f32)> + '_ {
+        let walkable = move |offset: IVec2| {
+            let neighbour = cell.as_ivec2() + offset;
+            (neighbour.x >= 0 && neighbour.y >= 0 && self.is_walkable(neighbour.as_uvec2()))
+                .then(|| neighbour.as_uvec2())
+        };
+        (-1..=1)
+            .flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y)))
+            .filter(|offset| *offset != IVec2::ZERO)
+            .filter_map(move |offset| {
+                let neighbour = walkable(offset)?;
+                if offset.x != 0 && offset.y != 0 {
+                    // Don't squeeze diagonally between two blocked cells
+                    walkable(IVec2::new(offset.x, 0))?;
+                    walkable(IVec2::new(0, offset.y))?;
+                    return Some((neighbour, SQRT_2 * self.cell_size));
+                }
+                Some((neighbour, self.cell_size))
+            })
+    }
+
+    /// Octile distance between two cells.
+    fn heuristic(&self, from: UVec2, to: UVec2) -> f32 {
+        let delta = (from.as_ivec2() - to.as_ivec2()).abs().as_vec2();
+        (delta.max_element() + (SQRT_2 - 1.0) * delta.min_element()) * self.cell_size
+    }
+}
+
+struct OpenCell {
+    cell: UVec2,
+    estimate: f32,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    /// Reversed so the [`BinaryHeap`] pops the lowest estimate first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+/// Blocks the cells below static colliders and updates them when the collider moves.
+fn bake_obstacles(
+    mut commands: Commands,
+    mut nav_grid: ResMut<NavGrid>,
+    colliders: Query<
+        (Entity, &ColliderAabb, &ColliderOf, Option<&NavFootprint>),
+        (Changed<ColliderAabb>, Without<Terrain>),
+    >,
+    bodies: Query<&RigidBody>,
+    heightmap: Option<Res<Heightmap>>,
+) {
+    for (entity, aabb, collider_of, previous) in colliders.iter() {
+        if !bodies.get(collider_of.body).is_ok_and(RigidBody::is_static) {
+            continue;
+        }
+        let center = aabb.center().xz();
+        let ground = heightmap
+            .as_ref()
+            .map_or(0.0, |heightmap| heightmap.height_at(center.x, center.y));
+        let footprint = (aabb.max.y - ground > STEP_HEIGHT && aabb.min.y - ground < CLEARANCE)
+            .then(|| nav_grid.footprint(aabb.min.xz(), aabb.max.xz()))
+            .flatten();
+        if footprint == previous.copied() {
+            continue;
+        }
+
+        match footprint {
+            Some(footprint) => {
+                if let Some(previous) = previous {
+                    nav_grid.unblock(*previous);
+                }
+                nav_grid.block(footprint);
+                commands.entity(entity).insert(footprint);
+            }
+            // Releasing the cells is handled by `remove_obstacle`
+            None => {
+                commands.entity(entity).remove::<NavFootprint>();
+            }
+        }
+    }
+}
+
+fn remove_obstacle(
+    remove: On<Remove, NavFootprint>,
+    footprints: Query<&NavFootprint>,
+    mut nav_grid: ResMut<NavGrid>,
+) {
+    if let Ok(footprint) = footprints.get(remove.entity) {
+        nav_grid.unblock(*footprint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid of 7 by 7 cells of size one, cell `(0, 0)` is centered at `(-3, -3)`.
+    fn grid(blocked: &[(u32, u32)]) -> NavGrid {
+        let mut grid = NavGrid::new(Vec2::splat(7.0), 1.0);
+        for &(x, y) in blocked {
+            let cell = UVec2::new(x, y);
+            grid.block(NavFootprint {
+                min: cell,
+                max: cell,
+            });
+        }
+        grid
+    }
+
+    fn center(grid: &NavGrid, x: u32, y: u32) -> Vec2 {
+        grid.cell_center(UVec2::new(x, y))
+    }
+
+    #[test]
+    fn path_to_the_own_cell_is_the_goal() {
+        let grid = grid(&[]);
+        let position = Vec2::new(0.2, -0.3);
+        assert_eq!(grid.find_path(position, position), Some(vec![position]));
+    }
+
+    #[test]
+    fn path_ends_exactly_at_the_goal() {
+        let grid = grid(&[]);
+        let goal = center(&grid, 6, 0) + Vec2::new(0.3, 0.1);
+        let path = grid.find_path(center(&grid, 0, 0), goal).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&goal));
+    }
+
+    #[test]
+    fn path_avoids_blocked_cells() {
+        // A wall with a gap at the top
+        let wall = [(3, 0), (3, 1), (3, 2), (3, 3), (3, 4), (3, 5)];
+        let grid = grid(&wall);
+        let goal = center(&grid, 6, 0);
+        let path = grid.find_path(center(&grid, 0, 0), goal).unwrap();
+
+        assert_eq!(path.last(), Some(&goal));
+        assert!(path.contains(&center(&grid, 3, 6)));
+        for waypoint in path {
+            assert!(grid.is_walkable(grid.cell_at(waypoint).unwrap()));
+        }
+    }
+
+    #[test]
+    fn path_does_not_cut_corners() {
+        let grid = grid(&[(1, 0)]);
+        let goal = center(&grid, 1, 1);
+        assert_eq!(
+            grid.find_path(center(&grid, 0, 0), goal),
+            Some(vec![center(&grid, 0, 1), goal])
+        );
+    }
+
+    #[test]
+    fn path_to_a_blocked_goal_ends_next_to_it() {
+        let grid = grid(&[(3, 3)]);
+        let goal = center(&grid, 3, 3);
+        let path = grid.find_path(center(&grid, 3, 0), goal).unwrap();
+        assert_eq!(path[path.len() - 2..], [center(&grid, 3, 2), goal]);
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_path() {
+        let wall: Vec<_> = (0..7).map(|y| (3, y)).collect();
+        let grid = grid(&wall);
+        assert_eq!(
+            grid.find_path(center(&grid, 0, 0), center(&grid, 6, 0)),
+            None
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+
+use crate::{GameState, PauseState};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(PauseState::Paused), pause_time)
+            .add_systems(OnExit(PauseState::Paused), unpause_time)
+            .add_systems(PostUpdate, grab_cursor);
+    }
+}
+
+/// UI that needs the cursor while the game is running, e.g. the research window.
+#[derive(Component, Debug, Default)]
+pub struct FreesCursor;
+
+/// Stops the virtual clock, which also halts physics and every timer ticked in `Update`.
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+/// Locks the cursor while playing and frees it in menus, while paused and for [`FreesCursor`] UI.
+fn grab_cursor(
+    mut cursor_options: Single<&mut CursorOptions, With<PrimaryWindow>>,
+    game_state: Res<State<GameState>>,
+    pause_state: Option<Res<State<PauseState>>>,
+    free_cursor_ui: Query<(), With<FreesCursor>>,
+) {
+    let playing = *game_state.get() == GameState::InGame
+        && pause_state.is_some_and(|state| *state.get() == PauseState::Running)
+        && free_cursor_ui.is_empty();
+    let (grab_mode, visible) = if playing {
+        (CursorGrabMode::Locked, false)
+    } else {
+        (CursorGrabMode::None, true)
+    };
+    if cursor_options.grab_mode != grab_mode || cursor_options.visible != visible {
+        cursor_options.grab_mode = grab_mode;
+        cursor_options.visible = visible;
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use avian3d::prelude::{Collider, RayCaster, RayHitData, RayHits, RigidBody, SpatialQueryFilter};
 use bevy::{
-    input::{ButtonState, keyboard::KeyboardInput, mouse::MouseMotion},
+    input::mouse::MouseMotion,
     prelude::*,
     render::view::{ColorGrading, ColorGradingGlobal, ColorGradingSection, Hdr},
     window::{CursorGrabMode, CursorOptions, PrimaryWindow},
@@ -12,7 +12,28 @@ use puppeteer::{
     puppeteer::{Puppeteer, PuppeteerInput},
 };
 
-use crate::GameState;
+use crate::{
+    GameState, PauseState,
+    health::{DespawnPolicy, Health},
+    input::{Action, InputActions},
+    settings::Settings,
+    tool::HeldTool,
+    world::chunk::ChunkAnchor,
+};
+
+/// Maximum distance to hit things, e.g. to chop trees.
+pub const HIT_REACH: f32 = 3.0;
+
+/// Maximum distance to interact with the world, e.g. to place buildings.
+pub const INTERACTION_REACH: f32 = 12.0;
+
+/// Damage of a single hit by the player.
+pub const PLAYER_DAMAGE: f32 = 10.0;
+
+pub const PLAYER_HEALTH: f32 = 100.0;
+
+/// Where the player starts, above the ground so they fall onto it.
+pub const PLAYER_SPAWN: Vec3 = Vec3::new(0.0, 5.5, 0.0);
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
@@ -20,8 +41,7 @@ impl Plugin for PlayerPlugin {
         app.add_systems(OnEnter(GameState::InGame), spawn_player)
             .add_systems(
                 Update,
-                (mouse_lock, player_look, player_move, world_interaction)
-                    .run_if(in_state(GameState::InGame)),
+                (player_look, player_move, world_interaction).run_if(in_state(PauseState::Running)),
             );
     }
 }
@@ -33,10 +53,16 @@ fn spawn_player(mut commands: Commands, _asset_server: Res<AssetServer>) {
     let player_body = commands
         .spawn((
             Player,
+            HeldTool::default(),
+            ChunkAnchor::ViewDistance,
+            Health::new(PLAYER_HEALTH),
+            // Stays around for a game over
+            DespawnPolicy::LeaveCorpse,
             Puppeteer::default(),
             Collider::capsule(0.25, 1.80),
             RigidBody::Kinematic,
-            Transform::from_xyz(0.0, 5.5, 0.0),
+            Transform::from_translation(PLAYER_SPAWN),
+            DespawnOnExit(GameState::InGame),
         ))
         .id();
     commands.spawn((
@@ -46,13 +72,16 @@ fn spawn_player(mut commands: Commands, _asset_server: Res<AssetServer>) {
         },
         RelatedPuppet::new(player_body),
         Camera3d::default(),
+        SpatialListener::new(0.3),
         // Raycaster for world interaction
         RayCaster::new(Vec3::ZERO, Dir3::NEG_Z)
             .with_ignore_self(true)
             .with_query_filter(SpatialQueryFilter::from_excluded_entities(vec![
                 player_body,
             ]))
-            .with_max_distance(3.0),
+            .with_max_distance(INTERACTION_REACH)
+            // Only the closest hit
+            .with_max_hits(1),
         Hdr,
         ColorGrading {
             global: ColorGradingGlobal {
@@ -86,34 +115,20 @@ fn spawn_player(mut commands: Commands, _asset_server: Res<AssetServer>) {
             },
         },
         PrimaryEguiContext,
+        DespawnOnExit(GameState::InGame),
     ));
 }
 
-fn mouse_lock(
-    mut query: Query<&mut CursorOptions, With<PrimaryWindow>>,
-    keys: Res<ButtonInput<KeyCode>>,
-) {
-    if !keys.just_pressed(KeyCode::Escape) {
-        return;
-    }
-    let Ok(mut cursor_options) = query.single_mut() else {
-        return;
-    };
-
-    if cursor_options.grab_mode != CursorGrabMode::Locked {
-        cursor_options.grab_mode = CursorGrabMode::Locked;
-        cursor_options.visible = false;
-    } else {
-        cursor_options.grab_mode = CursorGrabMode::None;
-        cursor_options.visible = true;
-    }
-}
 pub fn player_look(
     mut player_head_query: Query<&mut PuppetRig, Without<Player>>,
     mut mouse_motion_event: MessageReader<MouseMotion>,
     window: Single<&CursorOptions, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    actions: Res<InputActions>,
+    time: Res<Time>,
 ) -> Result {
-    let sensibility = 0.75;
+    let sensibility = settings.mouse_sensitivity;
+    let stick = actions.look() * (settings.stick_look_speed * time.delta_secs()).to_radians();
     for mut head in player_head_query.iter_mut() {
         for mouse in mouse_motion_event.read() {
             if window.grab_mode == CursorGrabMode::None {
@@ -121,9 +136,13 @@ pub fn player_look(
             }
             head.pitch -= (0.1 * mouse.delta.y * sensibility).to_radians();
             head.yaw -= (0.1 * mouse.delta.x * sensibility).to_radians();
-
-            head.pitch = head.pitch.clamp(-1.54, 1.54);
         }
+        if window.grab_mode != CursorGrabMode::None {
+            head.pitch += stick.y;
+            head.yaw -= stick.x;
+        }
+
+        head.pitch = head.pitch.clamp(-1.54, 1.54);
     }
     Ok(())
 }
@@ -131,26 +150,10 @@ pub fn player_look(
 pub fn player_move(
     player_head_query: Query<&PuppetRig>,
     mut player_query: Query<(&mut PuppeteerInput, &mut Puppeteer)>,
-    mut keyboard_input: Local<ButtonInput<KeyCode>>,
-    mut keyboard_input_events: MessageReader<KeyboardInput>,
+    actions: Res<InputActions>,
 ) -> Result {
-    keyboard_input.clear();
-    for event in keyboard_input_events.read() {
-        let key_code = event.key_code;
-        match event.state {
-            ButtonState::Pressed => keyboard_input.press(key_code),
-            ButtonState::Released => keyboard_input.release(key_code),
-        }
-    }
-
-    let up = keyboard_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]);
-    let down = keyboard_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]);
-    let left = keyboard_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]);
-    let right = keyboard_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]);
-
-    let horizontal = right as i8 - left as i8;
-    let vertical = up as i8 - down as i8;
-    let direction = Vec3::new(horizontal as f32, 0.0, vertical as f32).clamp_length_max(1.0);
+    let movement = actions.movement();
+    let direction = Vec3::new(movement.x, 0.0, movement.y);
 
     let head = player_head_query.single()?;
     let (mut input, _puppeteer) = player_query.single_mut()?;
@@ -166,22 +169,22 @@ pub fn player_move(
     let mut move_vector = Vec3::ZERO;
     move_vector += forward * direction.z;
     move_vector += right * direction.x;
-    move_vector = move_vector.normalize_or_zero();
+    // Keeps partial stick deflections slower
+    move_vector = move_vector.clamp_length_max(1.0);
 
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    if actions.just_pressed(Action::Jump) {
         input.start_jump();
     }
-    if keyboard_input.just_released(KeyCode::Space) {
+    if actions.just_released(Action::Jump) {
         input.stop_jump();
     }
-    if keyboard_input.pressed(KeyCode::ShiftLeft) {
+    if actions.pressed(Action::Sprint) {
         input.speed_multiplier = 2.0
     } else {
         input.speed_multiplier = 1.0
     }
 
     input.move_amount(move_vector);
-    //println!("{:?}", move_vector);
     Ok(())
 }
 
@@ -189,24 +192,31 @@ pub fn player_move(
 #[entity_event(propagate)]
 #[entity_event(auto_propagate)]
 pub struct PlayerInteraction {
-    entity: Entity,
-    hit: RayHitData,
+    pub entity: Entity,
+    pub hit: RayHitData,
 }
 
 #[derive(EntityEvent)]
 #[entity_event(propagate)]
 #[entity_event(auto_propagate)]
 pub struct PlayerHit {
-    entity: Entity,
-    hit: RayHitData,
+    pub entity: Entity,
+    pub hit: RayHitData,
+    /// Where the hit landed in world space.
+    pub point: Vec3,
 }
 
 pub fn world_interaction(
     mut commands: Commands,
-    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    actions: Res<InputActions>,
     rays: Query<(&RayCaster, &RayHits), With<PuppetRig>>,
+    window: Single<&CursorOptions, With<PrimaryWindow>>,
 ) {
-    if !mouse_buttons.any_just_pressed(vec![MouseButton::Right, MouseButton::Left]) {
+    // Clicks on open UI don't reach the world
+    if window.grab_mode == CursorGrabMode::None {
+        return;
+    }
+    if !actions.just_pressed(Action::Interact) {
         return;
     }
     for (_ray_caster, ray_hits) in rays.iter() {
@@ -214,16 +224,9 @@ pub fn world_interaction(
             continue;
         };
 
-        if mouse_buttons.just_pressed(MouseButton::Right) {
-            commands.trigger(PlayerInteraction {
-                entity: first_hit.entity,
-                hit: *first_hit,
-            });
-        } else {
-            commands.trigger(PlayerHit {
-                entity: first_hit.entity,
-                hit: *first_hit,
-            });
-        };
+        commands.trigger(PlayerInteraction {
+            entity: first_hit.entity,
+            hit: *first_hit,
+        });
     }
 }
@@ -0,0 +1,340 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+
+use crate::{
+    GameState,
+    building::catalog::BuildingDefinition,
+    game_resources::{Cost, InsufficientResources, ResourceKind, Treasury},
+};
+
+/// Path of the tech tree below the assets folder.
+pub const TECH_TREE_PATH: &str = "tech_tree.ron";
+
+/// Ids of researchable upgrades that systems check with [`UnlockedFeatures`].
+pub mod upgrades {
+    /// Every harvesting hit yields one extra resource.
+    pub const SHARP_TOOLS: &str = "sharp_tools";
+}
+
+pub struct ResearchPlugin;
+
+impl Plugin for ResearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TechTreeDefinition>()
+            .init_asset_loader::<TechTreeLoader>()
+            .init_resource::<TechTree>()
+            .add_observer(research)
+            .add_systems(Startup, load_tech_tree)
+            .add_systems(OnEnter(GameState::InGame), reset_research)
+            .add_systems(Update, update_tech_tree);
+    }
+}
+
+/// Every research node, see `assets/tech_tree.ron`.
+#[derive(Asset, TypePath, Debug, Clone, Default)]
+pub struct TechTreeDefinition {
+    nodes: Vec<TechNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TechNode {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub cost: Cost,
+    /// Nodes that have to be researched first.
+    pub requires: Vec<String>,
+}
+
+/// The loaded research nodes and which of them have been researched.
+#[derive(Resource, Debug, Default)]
+pub struct TechTree {
+    nodes: Vec<TechNode>,
+    researched: HashSet<String>,
+}
+
+impl TechTree {
+    /// Nodes in the order of the data file, prerequisites come before the nodes requiring them.
+    pub fn nodes(&self) -> &[TechNode] {
+        &self.nodes
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TechNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    pub fn is_researched(&self, id: &str) -> bool {
+        self.researched.contains(id)
+    }
+
+    pub fn researched(&self) -> impl Iterator<Item = &str> {
+        self.researched.iter().map(String::as_str)
+    }
+
+    /// Returns the node if it can be researched right now, ignoring its cost.
+    pub fn available(&self, id: &str) -> Result<&TechNode, ResearchError> {
+        let Some(node) = self.get(id) else {
+            return Err(ResearchError::Unknown(id.to_owned()));
+        };
+        if self.is_researched(id) {
+            return Err(ResearchError::AlreadyResearched(id.to_owned()));
+        }
+        let missing: Vec<String> = node
+            .requires
+            .iter()
+            .filter(|required| !self.is_researched(required))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(ResearchError::MissingPrerequisites {
+                node: id.to_owned(),
+                missing,
+            });
+        }
+        Ok(node)
+    }
+
+    /// Marks the node as researched without any checks, e.g. when loading a save.
+    pub fn complete(&mut self, id: impl Into<String>) {
+        self.researched.insert(id.into());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResearchError {
+    Unknown(String),
+    AlreadyResearched(String),
+    MissingPrerequisites { node: String, missing: Vec<String> },
+    InsufficientResources(InsufficientResources),
+}
+
+impl Display for ResearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResearchError::Unknown(id) => write!(f, "There is no research `{id}`"),
+            ResearchError::AlreadyResearched(id) => write!(f, "`{id}` is already researched"),
+            ResearchError::MissingPrerequisites { node, missing } => write!(
+                f,
+                "`{node}` first requires researching {}",
+                missing.join(", ")
+            ),
+            ResearchError::InsufficientResources(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for ResearchError {}
+
+/// Researches the node with `id`, paying its cost.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct Research {
+    pub id: String,
+}
+
+/// Triggered after a node was researched.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct Researched {
+    pub id: String,
+}
+
+/// Read access to what has been researched, for gating features on it.
+#[derive(SystemParam)]
+pub struct UnlockedFeatures<'w> {
+    tech_tree: Res<'w, TechTree>,
+}
+
+impl UnlockedFeatures<'_> {
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.tech_tree.is_researched(id)
+    }
+
+    pub fn all_unlocked(&self, ids: &[String]) -> bool {
+        ids.iter().all(|id| self.is_unlocked(id))
+    }
+
+    pub fn building_unlocked(&self, building: &BuildingDefinition) -> bool {
+        self.all_unlocked(&building.requires)
+    }
+}
+
+#[derive(Resource)]
+pub struct TechTreeHandle(pub Handle<TechTreeDefinition>);
+
+fn load_tech_tree(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TechTreeHandle(asset_server.load(TECH_TREE_PATH)));
+}
+
+/// Starts a new game without any research.
+fn reset_research(mut tech_tree: ResMut<TechTree>) {
+    tech_tree.researched.clear();
+}
+
+/// Takes over the nodes whenever the tech tree is (re)loaded, keeping what has been researched.
+fn update_tech_tree(
+    mut asset_events: MessageReader<AssetEvent<TechTreeDefinition>>,
+    mut tech_tree: ResMut<TechTree>,
+    definitions: Res<Assets<TechTreeDefinition>>,
+    handle: Res<TechTreeHandle>,
+) {
+    for event in asset_events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != handle.0.id() {
+            continue;
+        }
+        let Some(definition) = definitions.get(*id) else {
+            continue;
+        };
+        tech_tree.nodes = definition.nodes.clone();
+    }
+}
+
+fn research(
+    research: On<Research>,
+    mut commands: Commands,
+    mut tech_tree: ResMut<TechTree>,
+    mut treasury: Treasury,
+) {
+    let cost = match tech_tree.available(&research.id) {
+        Ok(node) => node.cost,
+        Err(error) => {
+            warn!("Can't research: {error}");
+            return;
+        }
+    };
+    if let Err(error) = treasury.try_spend(&cost) {
+        warn!(
+            "Can't research: {}",
+            ResearchError::InsufficientResources(error)
+        );
+        return;
+    }
+    tech_tree.complete(research.id.clone());
+    commands.trigger(Researched {
+        id: research.id.clone(),
+    });
+}
+
+#[derive(Deserialize)]
+struct TechTreeFile {
+    nodes: Vec<TechNodeEntry>,
+}
+
+#[derive(Deserialize)]
+struct TechNodeEntry {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    cost: HashMap<String, u32>,
+    #[serde(default)]
+    requires: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum TechTreeError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    DuplicateId(String),
+    UnknownResourceKind { node: String, kind: String },
+    UndefinedPrerequisite { node: String, required: String },
+}
+
+impl Display for TechTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TechTreeError::Io(error) => write!(f, "Could not read the tech tree: {error}"),
+            TechTreeError::Ron(error) => write!(f, "Invalid tech tree: {error}"),
+            TechTreeError::DuplicateId(id) => {
+                write!(f, "Research `{id}` is defined more than once")
+            }
+            TechTreeError::UnknownResourceKind { node, kind } => write!(
+                f,
+                "Research `{node}` costs unknown resource `{kind}`, expected one of {}",
+                ResourceKind::ALL.map(|kind| kind.to_string()).join(", ")
+            ),
+            TechTreeError::UndefinedPrerequisite { node, required } => write!(
+                f,
+                "Research `{node}` requires `{required}`, which has to be defined before it"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TechTreeError {}
+
+#[derive(Default)]
+pub struct TechTreeLoader;
+
+impl AssetLoader for TechTreeLoader {
+    type Asset = TechTreeDefinition;
+    type Settings = ();
+    type Error = TechTreeError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(TechTreeError::Io)?;
+        let file: TechTreeFile = ron::de::from_bytes(&bytes).map_err(TechTreeError::Ron)?;
+
+        let mut nodes: Vec<TechNode> = Vec::with_capacity(file.nodes.len());
+        for entry in file.nodes {
+            if nodes.iter().any(|node| node.id == entry.id) {
+                return Err(TechTreeError::DuplicateId(entry.id));
+            }
+            // Only allowing earlier nodes as prerequisites rules out cycles
+            if let Some(required) = entry
+                .requires
+                .iter()
+                .find(|required| !nodes.iter().any(|node| node.id == **required))
+            {
+                return Err(TechTreeError::UndefinedPrerequisite {
+                    required: required.clone(),
+                    node: entry.id,
+                });
+            }
+
+            let cost = match Cost::from_names(entry.cost) {
+                Ok(cost) => cost,
+                Err(kind) => {
+                    return Err(TechTreeError::UnknownResourceKind {
+                        node: entry.id,
+                        kind,
+                    });
+                }
+            };
+
+            nodes.push(TechNode {
+                id: entry.id,
+                name: entry.name,
+                description: entry.description,
+                cost,
+                requires: entry.requires,
+            });
+        }
+        Ok(TechTreeDefinition { nodes })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
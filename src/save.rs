@@ -0,0 +1,596 @@
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use ron::{Value, ser::PrettyConfig};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState,
+    building::{Building, BuildingAssets, catalog::Catalog, spawn_building},
+    day_night::DayNightCycle,
+    game_resources::{Cost, GameResources, Treasury},
+    harvestable::{Depleted, HarvestableId, restore_depleted},
+    health::Health,
+    player::{PLAYER_SPAWN, Player},
+    research::TechTree,
+    wave::{DayStarted, WaveDirector},
+    world::{
+        WorldSeed,
+        chunk::{NodeChange, NodeChanges, stream_chunks},
+    },
+};
+
+/// Version written to new saves. Bump it and add a migration whenever [`SaveData`] changes.
+pub const SAVE_VERSION: u32 = 1;
+
+pub const SAVE_PATH: &str = "saves/savegame.ron";
+/// Written at the end of every day.
+pub const AUTOSAVE_PATH: &str = "saves/autosave.ron";
+
+/// Upgrades the fields of a save by one version.
+type Migration = fn(&mut ron::Map) -> Result<(), String>;
+
+/// `MIGRATIONS[i]` upgrades a save from version `i + 1` to `i + 2`.
+const MIGRATIONS: &[Migration] = &[];
+const _: () = assert!(MIGRATIONS.len() == SAVE_VERSION as usize - 1);
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(save)
+            .add_observer(load)
+            .add_observer(autosave)
+            .add_systems(
+                Update,
+                apply_pending_load
+                    // Saved nodes are restored by their chunks, which load in the same frame
+                    .before(stream_chunks)
+                    .run_if(in_state(GameState::InGame).and(resource_exists::<PendingLoad>)),
+            )
+            .add_systems(
+                Update,
+                spawn_pending_buildings
+                    .run_if(in_state(GameState::InGame).and(resource_exists::<PendingBuildings>)),
+            )
+            .add_systems(OnExit(GameState::InGame), drop_pending_buildings);
+    }
+}
+
+/// Everything needed to continue a game. The world itself is generated again from the seed,
+/// so only changes to it are stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveData {
+    pub version: u32,
+    pub seed: u64,
+    pub day: u32,
+    pub time_of_day: f32,
+    pub resources: Cost,
+    pub researched: Vec<String>,
+    pub player: SavedTransform,
+    /// Only nodes that were harvested.
+    pub nodes: Vec<SavedNode>,
+    pub buildings: Vec<SavedBuilding>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+}
+
+impl From<&Transform> for SavedTransform {
+    fn from(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+        }
+    }
+}
+
+impl From<SavedTransform> for Transform {
+    fn from(saved: SavedTransform) -> Self {
+        Transform::from_translation(Vec3::from_array(saved.translation))
+            .with_rotation(Quat::from_array(saved.rotation))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedNode {
+    pub id: u32,
+    pub health: f32,
+    /// Seconds until a depleted node respawns.
+    pub respawn_in: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedBuilding {
+    pub id: String,
+    pub transform: SavedTransform,
+    pub health: f32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Ron(ron::Error),
+    Parse(ron::error::SpannedError),
+    /// The file is not a save or lacks its version.
+    MissingVersion,
+    /// Written by a newer version of the game.
+    UnsupportedVersion(u32),
+    Migration {
+        version: u32,
+        message: String,
+    },
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "Could not access the save: {error}"),
+            SaveError::Ron(error) => write!(f, "Invalid save: {error}"),
+            SaveError::Parse(error) => write!(f, "Invalid save: {error}"),
+            SaveError::MissingVersion => write!(f, "The save has no version"),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "Save version {version} is not supported, expected at most {SAVE_VERSION}"
+            ),
+            SaveError::Migration { version, message } => {
+                write!(f, "Could not upgrade save version {version}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// Saves the running game to `path`.
+#[derive(Event, Debug, Clone)]
+pub struct SaveGame {
+    pub path: PathBuf,
+}
+
+/// Loads the save at `path` and enters the game.
+#[derive(Event, Debug, Clone)]
+pub struct LoadGame {
+    pub path: PathBuf,
+}
+
+/// A loaded save that is applied once the world has been generated.
+#[derive(Resource, Debug)]
+pub struct PendingLoad(pub SaveData);
+
+/// Buildings of a loaded save, spawned once the catalog and their assets are loaded.
+#[derive(Resource, Debug)]
+struct PendingBuildings(Vec<SavedBuilding>);
+
+/// Captures the running game from `world`.
+pub fn capture(world: &mut World) -> SaveData {
+    let mut nodes: Vec<SavedNode> = world
+        .query::<(&HarvestableId, &Health, Option<&Depleted>)>()
+        .iter(world)
+        .filter(|(_, health, depleted)| depleted.is_some() || health.current < health.max)
+        .map(|(id, health, depleted)| SavedNode {
+            id: id.0,
+            health: health.current,
+            respawn_in: depleted.map(|depleted| depleted.remaining().as_secs_f32()),
+        })
+        .collect();
+    // Nodes of unloaded chunks
+    let now = world.resource::<Time<Virtual>>().elapsed();
+    nodes.extend(world.resource::<NodeChanges>().iter().map(|(id, change)| {
+        SavedNode {
+            id: id.0,
+            health: change.health,
+            respawn_in: change
+                .respawn_at
+                .map(|respawn_at| respawn_at.saturating_sub(now).as_secs_f32()),
+        }
+    }));
+    let mut buildings: Vec<_> = world
+        .query::<(&Building, &Transform, &Health)>()
+        .iter(world)
+        .map(|(building, transform, health)| SavedBuilding {
+            id: building.id.clone(),
+            transform: transform.into(),
+            health: health.current,
+        })
+        .collect();
+    // Loaded, but still waiting for the catalog
+    if let Some(pending) = world.get_resource::<PendingBuildings>() {
+        buildings.extend(pending.0.iter().cloned());
+    }
+    let player = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .next()
+        .map_or(
+            SavedTransform {
+                translation: PLAYER_SPAWN.to_array(),
+                rotation: Quat::IDENTITY.to_array(),
+            },
+            SavedTransform::from,
+        );
+
+    SaveData {
+        version: SAVE_VERSION,
+        seed: world.resource::<WorldSeed>().0,
+        day: world.resource::<WaveDirector>().day(),
+        time_of_day: world.resource::<DayNightCycle>().time_of_day,
+        resources: world.resource::<GameResources>().stock(),
+        researched: world
+            .resource::<TechTree>()
+            .researched()
+            .map(str::to_owned)
+            .collect(),
+        player,
+        nodes,
+        buildings,
+    }
+}
+
+/// Writes the running game to `path`.
+pub fn save_game(world: &mut World, path: impl AsRef<Path>) -> Result<(), SaveError> {
+    let data = capture(world);
+    let text =
+        ron::ser::to_string_pretty(&data, PrettyConfig::default()).map_err(SaveError::Ron)?;
+    if let Some(directory) = path.as_ref().parent() {
+        fs::create_dir_all(directory).map_err(SaveError::Io)?;
+    }
+    fs::write(path, text).map_err(SaveError::Io)
+}
+
+/// Reads the save at `path`, upgrading it from older versions.
+pub fn load_game(path: impl AsRef<Path>) -> Result<SaveData, SaveError> {
+    let text = fs::read_to_string(path).map_err(SaveError::Io)?;
+    let Value::Map(mut fields) = ron::from_str::<Value>(&text).map_err(SaveError::Parse)? else {
+        return Err(SaveError::MissingVersion);
+    };
+    let version_key = Value::String("version".to_owned());
+    let version = fields
+        .get(&version_key)
+        .cloned()
+        .and_then(|version| version.into_rust::<u32>().ok())
+        .ok_or(SaveError::MissingVersion)?;
+    if version == 0 {
+        return Err(SaveError::MissingVersion);
+    }
+    if version > SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+
+    for (from, migration) in (version..).zip(&MIGRATIONS[version as usize - 1..]) {
+        migration(&mut fields).map_err(|message| SaveError::Migration {
+            version: from,
+            message,
+        })?;
+    }
+    fields.insert(version_key, Value::from(SAVE_VERSION));
+    Value::Map(fields).into_rust().map_err(SaveError::Ron)
+}
+
+/// The most recently written save, if there is any.
+pub fn latest_save() -> Option<PathBuf> {
+    [SAVE_PATH, AUTOSAVE_PATH]
+        .into_iter()
+        .filter_map(|path| {
+            let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+            Some((modified, PathBuf::from(path)))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+fn save(save: On<SaveGame>, mut commands: Commands) {
+    let path = save.path.clone();
+    commands.queue(move |world: &mut World| match save_game(world, &path) {
+        Ok(()) => info!("Saved the game to {}", path.display()),
+        Err(error) => error!("Could not save the game to {}: {error}", path.display()),
+    });
+}
+
+fn load(load: On<LoadGame>, mut commands: Commands, mut next_state: ResMut<NextState<GameState>>) {
+    match load_game(&load.path) {
+        Ok(data) => {
+            // The world has to be generated from the same seed before the save applies
+            commands.insert_resource(WorldSeed(data.seed));
+            commands.insert_resource(PendingLoad(data));
+            next_state.set(GameState::InGame);
+        }
+        Err(error) => error!("Could not load {}: {error}", load.path.display()),
+    }
+}
+
+fn autosave(_day_started: On<DayStarted>, mut commands: Commands) {
+    commands.trigger(SaveGame {
+        path: AUTOSAVE_PATH.into(),
+    });
+}
+
+fn apply_pending_load(
+    mut commands: Commands,
+    pending: Res<PendingLoad>,
+    mut treasury: Treasury,
+    mut tech_tree: ResMut<TechTree>,
+    mut wave_director: ResMut<WaveDirector>,
+    mut cycle: ResMut<DayNightCycle>,
+    mut player: Query<&mut Transform, With<Player>>,
+    mut nodes: Query<(Entity, &HarvestableId, &mut Health)>,
+    mut node_changes: ResMut<NodeChanges>,
+    time: Res<Time<Virtual>>,
+) {
+    commands.remove_resource::<PendingLoad>();
+    let data = &pending.0;
+    treasury.set_stock(data.resources);
+    for id in &data.researched {
+        tech_tree.complete(id.clone());
+    }
+    wave_director.set_day(data.day);
+    cycle.set_time_of_day(data.time_of_day);
+    if let Ok(mut transform) = player.single_mut() {
+        *transform = data.player.into();
+    }
+
+    let mut loaded = HashSet::new();
+    for (entity, id, mut health) in nodes.iter_mut() {
+        loaded.insert(*id);
+        let Some(saved) = data.nodes.iter().find(|node| node.id == id.0) else {
+            continue;
+        };
+        health.current = saved.health;
+        if let Some(respawn_in) = saved.respawn_in {
+            health.current = 0.0;
+            restore_depleted(&mut commands, entity, Duration::from_secs_f32(respawn_in));
+        }
+    }
+    // The chunks of the other nodes restore them once they load
+    for saved in &data.nodes {
+        if loaded.contains(&HarvestableId(saved.id)) {
+            continue;
+        }
+        node_changes.insert(
+            HarvestableId(saved.id),
+            NodeChange {
+                health: saved.health,
+                respawn_at: saved
+                    .respawn_in
+                    .map(|respawn_in| time.elapsed() + Duration::from_secs_f32(respawn_in)),
+            },
+        );
+    }
+
+    commands.insert_resource(PendingBuildings(data.buildings.clone()));
+}
+
+fn spawn_pending_buildings(
+    mut commands: Commands,
+    pending: Res<PendingBuildings>,
+    catalog: Catalog,
+    building_assets: Res<BuildingAssets>,
+) {
+    let Some(catalog) = catalog.get() else {
+        return;
+    };
+    // The assets are created from the catalog after it loaded
+    if !catalog
+        .buildings()
+        .iter()
+        .all(|building| building_assets.contains(&building.id))
+    {
+        return;
+    }
+
+    commands.remove_resource::<PendingBuildings>();
+    for saved in &pending.0 {
+        let Some(building) = catalog.get(&saved.id) else {
+            warn!("Saved building `{}` is not in the catalog", saved.id);
+            continue;
+        };
+        let Some(entity) = spawn_building(
+            &mut commands,
+            &building_assets,
+            building,
+            saved.transform.into(),
+        ) else {
+            continue;
+        };
+        commands.entity(entity).insert(Health {
+            current: saved.health,
+            max: building.hp as f32,
+        });
+    }
+}
+
+/// Buildings that were still waiting for the catalog don't carry over into the next game.
+fn drop_pending_buildings(mut commands: Commands) {
+    commands.remove_resource::<PendingBuildings>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        building::{
+            catalog::{BuildingCatalog, BuildingCatalogHandle, BuildingDefinition},
+            update_building_assets,
+        },
+        game_resources::ResourcesChanged,
+        harvestable::HarvestablePlugin,
+        health::{DamageEvent, DamageKind, HealthPlugin},
+        leaf_material::LeafAssets,
+        settings::Settings,
+        testing::headless_app,
+        world::{
+            Heightmap,
+            chunk::{ChunkAnchor, ChunkAssets, ChunkMap, ChunkPlugin, Prop, PropKind},
+            terrain::TerrainGenerator,
+        },
+    };
+
+    const TREE: HarvestableId = HarvestableId(7);
+    const TREE_POSITION: Vec3 = Vec3::new(10.0, 0.0, 10.0);
+    /// Far enough from the tree to unload its chunk.
+    const FAR_AWAY: Vec3 = Vec3::new(-150.0, 0.0, -150.0);
+
+    /// A world with a single tree, whose chunk streams around an anchor at `anchor`.
+    fn world_app(anchor: Vec3) -> App {
+        let mut app = headless_app();
+        app.insert_state(GameState::InGame)
+            .init_resource::<GameResources>()
+            .add_message::<ResourcesChanged>()
+            .init_resource::<TechTree>()
+            .init_resource::<WaveDirector>()
+            .init_resource::<DayNightCycle>()
+            .init_resource::<Settings>()
+            .init_resource::<BuildingAssets>()
+            .init_resource::<LeafAssets>()
+            .init_asset::<BuildingCatalog>()
+            .insert_resource(BuildingCatalogHandle(Handle::default()))
+            .insert_resource(WorldSeed(1))
+            .add_plugins((HealthPlugin, HarvestablePlugin, ChunkPlugin, SavePlugin));
+
+        app.insert_resource(ChunkMap::new([Prop {
+            kind: PropKind::Tree,
+            id: TREE,
+            transform: Transform::from_translation(TREE_POSITION),
+        }]))
+        .insert_resource(NodeChanges::default())
+        .insert_resource(ChunkAssets {
+            terrain_material: Handle::default(),
+            tree: Handle::default(),
+            rock_mesh: Handle::default(),
+            rock_material: Handle::default(),
+        })
+        .insert_resource::<Heightmap>(TerrainGenerator::new(1).generate());
+
+        app.world_mut().spawn((
+            ChunkAnchor::Radius(1.0),
+            GlobalTransform::from_translation(anchor),
+        ));
+        app
+    }
+
+    fn tree(app: &mut App) -> Option<Entity> {
+        let mut nodes = app.world_mut().query::<(Entity, &HarvestableId)>();
+        nodes
+            .iter(app.world())
+            .find(|(_, id)| **id == TREE)
+            .map(|(entity, _)| entity)
+    }
+
+    fn is_felled(app: &mut App) -> bool {
+        tree(app).is_some_and(|tree| {
+            app.world().get::<Depleted>(tree).is_some()
+                && app.world().get::<Health>(tree).unwrap().is_dead()
+        })
+    }
+
+    fn move_anchor(app: &mut App, position: Vec3) {
+        let mut anchors = app
+            .world_mut()
+            .query_filtered::<&mut GlobalTransform, With<ChunkAnchor>>();
+        *anchors.single_mut(app.world_mut()).unwrap() = GlobalTransform::from_translation(position);
+        app.update();
+        // Restoring the depletion takes another round of commands
+        app.update();
+    }
+
+    #[test]
+    fn felled_trees_survive_saving_and_loading() {
+        let mut app = world_app(TREE_POSITION);
+        app.update();
+        let tree = tree(&mut app).expect("the tree's chunk is loaded");
+        app.world_mut().trigger(DamageEvent {
+            entity: tree,
+            amount: PropKind::Tree.max_health(),
+            source: tree,
+            kind: DamageKind::Blunt,
+        });
+        app.update();
+        assert!(is_felled(&mut app));
+
+        // Unloading and reloading the chunk keeps it felled
+        move_anchor(&mut app, FAR_AWAY);
+        assert!(tree(&mut app).is_none());
+        move_anchor(&mut app, TREE_POSITION);
+        assert!(is_felled(&mut app));
+
+        let data = capture(app.world_mut());
+        let saved: Vec<_> = data.nodes.iter().filter(|node| node.id == TREE.0).collect();
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].respawn_in.is_some());
+
+        // The save applies before the chunk around the player spawns its tree
+        let mut loaded = world_app(TREE_POSITION);
+        loaded.insert_resource(PendingLoad(data));
+        loaded.update();
+        loaded.update();
+        assert!(is_felled(&mut loaded));
+
+        // Nothing stale is left to be saved again
+        let resaved = capture(loaded.world_mut());
+        assert_eq!(
+            resaved
+                .nodes
+                .iter()
+                .filter(|node| node.id == TREE.0)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn saved_buildings_wait_for_the_catalog() {
+        let mut app = world_app(TREE_POSITION);
+        app.add_systems(Update, update_building_assets);
+        app.update();
+        let mut data = capture(app.world_mut());
+        data.buildings.push(SavedBuilding {
+            id: "hut".to_owned(),
+            transform: (&Transform::from_xyz(5.0, 0.0, 5.0)).into(),
+            health: 40.0,
+        });
+
+        app.insert_resource(PendingLoad(data));
+        app.update();
+        app.update();
+        let mut buildings = app.world_mut().query::<(&Building, &Health)>();
+        assert_eq!(buildings.iter(app.world()).count(), 0);
+        // Saving in the meantime keeps them
+        assert_eq!(capture(app.world_mut()).buildings.len(), 1);
+
+        let catalog = app
+            .world_mut()
+            .resource_mut::<Assets<BuildingCatalog>>()
+            .add(BuildingCatalog::new(vec![BuildingDefinition {
+                id: "hut".to_owned(),
+                name: "Hut".to_owned(),
+                cost: Cost::default(),
+                hp: 100,
+                size: Vec3::ONE,
+                model: None,
+                color: Color::WHITE,
+                requires: Vec::new(),
+                tower: None,
+            }]));
+        app.world_mut()
+            .write_message(AssetEvent::LoadedWithDependencies { id: catalog.id() });
+        app.insert_resource(BuildingCatalogHandle(catalog));
+        app.update();
+        app.update();
+
+        let spawned: Vec<_> = buildings
+            .iter(app.world())
+            .map(|(building, health)| (building.id.as_str(), health.current))
+            .collect();
+        assert_eq!(spawned, [("hut", 40.0)]);
+        assert!(!app.world().contains_resource::<PendingBuildings>());
+    }
+}
@@ -0,0 +1,139 @@
+use std::{fs, time::Duration};
+
+use bevy::{
+    audio::{GlobalVolume, Volume},
+    prelude::*,
+    window::{PresentMode, PrimaryWindow},
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{day_night::Sun, input::InputBindings, world::fog_falloff};
+
+/// Config file the settings are persisted in.
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+/// Time the settings have to stay unchanged before they are written, so dragging a slider
+/// doesn't write the file every frame.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load()).add_systems(
+            Update,
+            (
+                apply_settings.run_if(resource_changed::<Settings>),
+                save_settings,
+            ),
+        );
+    }
+}
+
+/// Player preferences, written to [`SETTINGS_PATH`] shortly after they change.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub mouse_sensitivity: f32,
+    /// Turn rate at full right stick deflection, in degrees per second.
+    pub stick_look_speed: f32,
+    /// Stick deflection that is ignored, from 0 to 1.
+    pub stick_deadzone: f32,
+    /// Exponent of the stick response curve, 1 is linear.
+    pub stick_curve: f32,
+    pub ui_scale: f32,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub ui_volume: f32,
+    pub shadows: bool,
+    /// Distance up to which objects stay visible through the fog.
+    pub fog_distance: f32,
+    /// Distance around the player up to which the world is loaded.
+    pub view_distance: f32,
+    pub vsync: bool,
+    pub bindings: InputBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 0.75,
+            stick_look_speed: 180.0,
+            stick_deadzone: 0.15,
+            stick_curve: 2.0,
+            ui_scale: 4.0,
+            master_volume: 1.0,
+            music_volume: 0.7,
+            sfx_volume: 1.0,
+            ui_volume: 0.8,
+            shadows: true,
+            fog_distance: 15.0,
+            view_distance: 100.0,
+            vsync: true,
+            bindings: InputBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, falling back to the defaults if it is missing or invalid.
+    pub fn load() -> Self {
+        let Ok(text) = fs::read_to_string(SETTINGS_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&text).unwrap_or_else(|error| {
+            warn!("Invalid {SETTINGS_PATH}, using the default settings: {error}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|error| error.to_string())?;
+        fs::write(SETTINGS_PATH, text).map_err(|error| error.to_string())
+    }
+}
+
+fn apply_settings(
+    mut ui_scale: ResMut<UiScale>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    mut sun: Query<&mut DirectionalLight, With<Sun>>,
+    mut fogs: Query<&mut DistanceFog>,
+    settings: Res<Settings>,
+) {
+    ui_scale.0 = settings.ui_scale;
+    global_volume.volume = Volume::Linear(settings.master_volume);
+    if let Ok(mut window) = window.single_mut() {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+    for mut light in sun.iter_mut() {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut fog in fogs.iter_mut() {
+        fog.falloff = fog_falloff(settings.fog_distance);
+    }
+}
+
+fn save_settings(settings: Res<Settings>, mut delay: Local<Option<Timer>>, time: Res<Time<Real>>) {
+    // Loading them at startup is no change
+    if settings.is_changed() && !settings.is_added() {
+        *delay = Some(Timer::new(SAVE_DELAY, TimerMode::Once));
+    }
+    let Some(timer) = delay.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).is_finished() {
+        return;
+    }
+    *delay = None;
+    if let Err(error) = settings.save() {
+        warn!("Could not save the settings to {SETTINGS_PATH}: {error}");
+    }
+}
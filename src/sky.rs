@@ -0,0 +1,146 @@
+use bevy::{
+    light::{NotShadowCaster, NotShadowReceiver},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+    shader::ShaderRef,
+    transform::TransformSystems,
+};
+
+use crate::{GameState, day_night::DayNightCycle};
+
+const SKY_SHADER_ASSET_PATH: &str = "shaders/sky.wgsl";
+
+/// Radius of the sky sphere around the camera, within its far plane.
+const SKY_RADIUS: f32 = 900.0;
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<SkyMaterial>::default())
+            .init_resource::<SkySettings>()
+            .add_systems(OnEnter(GameState::InGame), spawn_sky)
+            .add_systems(Update, update_sky.run_if(in_state(GameState::InGame)))
+            .add_systems(
+                PostUpdate,
+                follow_camera
+                    .before(TransformSystems::Propagate)
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Look of the procedural sky. Its horizon takes the fog color of the [`DayNightCycle`], so
+/// distant objects fade into it, and its zenith the sky color.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct SkySettings {
+    /// How quickly the horizon color gives way to the zenith color, higher values give a thinner
+    /// band of horizon color.
+    pub horizon_falloff: f32,
+    /// Angular radius of the sun and moon in radians.
+    pub sun_size: f32,
+    /// Brightness of the sun disc, above 1 for bloom.
+    pub sun_brightness: f32,
+    /// Strength of the halo around the sun.
+    pub sun_glow: f32,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            horizon_falloff: 3.0,
+            sun_size: 0.03,
+            sun_brightness: 4.0,
+            sun_glow: 0.4,
+        }
+    }
+}
+
+/// Gradient from the horizon to the zenith with a sun disc, drawn on the inside of a sphere.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone, Default)]
+pub struct SkyMaterial {
+    #[uniform(0)]
+    pub sky: SkyUniform,
+}
+
+#[derive(ShaderType, Debug, Clone, Default)]
+pub struct SkyUniform {
+    pub zenith_color: LinearRgba,
+    pub horizon_color: LinearRgba,
+    pub sun_color: LinearRgba,
+    /// Towards the sun, in world space.
+    pub sun_direction: Vec3,
+    pub sun_size: f32,
+    pub sun_brightness: f32,
+    pub sun_glow: f32,
+    pub horizon_falloff: f32,
+}
+
+impl Material for SkyMaterial {
+    fn fragment_shader() -> ShaderRef {
+        SKY_SHADER_ASSET_PATH.into()
+    }
+}
+
+/// The sky sphere, which is kept centered on the camera.
+#[derive(Component, Debug)]
+struct Sky;
+
+fn spawn_sky(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+) {
+    // Inverted, so it is seen from the inside
+    let mesh = Sphere::new(SKY_RADIUS)
+        .mesh()
+        .uv(32, 18)
+        .with_inverted_winding()
+        .unwrap();
+    commands.spawn((
+        Name::new("Sky"),
+        Sky,
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(SkyMaterial::default())),
+        NotShadowCaster,
+        NotShadowReceiver,
+        DespawnOnExit(GameState::InGame),
+    ));
+}
+
+fn update_sky(
+    sky: Query<&MeshMaterial3d<SkyMaterial>, With<Sky>>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+    cycle: Res<DayNightCycle>,
+    settings: Res<SkySettings>,
+) {
+    let lighting = cycle.lighting();
+    for material in sky.iter() {
+        let Some(mut material) = materials.get_mut(material) else {
+            continue;
+        };
+        material.sky = SkyUniform {
+            zenith_color: lighting.sky_color.into(),
+            horizon_color: lighting.fog_color.into(),
+            sun_color: lighting.sun_color.into(),
+            // The sun shines along its forward direction
+            sun_direction: cycle.sun_rotation() * Vec3::Z,
+            sun_size: settings.sun_size,
+            sun_brightness: settings.sun_brightness,
+            sun_glow: settings.sun_glow,
+            horizon_falloff: settings.horizon_falloff,
+        };
+    }
+}
+
+fn follow_camera(
+    mut sky: Query<&mut Transform, With<Sky>>,
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    let Ok(camera) = camera.single() else {
+        return;
+    };
+    for mut transform in sky.iter_mut() {
+        transform.translation = camera.translation();
+    }
+}
@@ -0,0 +1,32 @@
+//! Helpers for headless tests of the game's plugins.
+
+use std::time::Duration;
+
+use bevy::{audio::AudioSource, prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+use crate::leaf_material::LeafMaterial;
+
+/// Time every update of a [`headless_app`] advances by.
+pub const STEP: Duration = Duration::from_millis(100);
+
+/// An app without a window or renderer. It has the asset types the game's plugins create on
+/// startup, and its clock advances by [`STEP`] on every update.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+        .init_asset::<Mesh>()
+        .init_asset::<Image>()
+        .init_asset::<Scene>()
+        .init_asset::<StandardMaterial>()
+        .init_asset::<LeafMaterial>()
+        .init_asset::<AudioSource>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(STEP));
+    app
+}
+
+/// Runs updates until `duration` passed on the clock.
+pub fn advance(app: &mut App, duration: Duration) {
+    for _ in 0..duration.div_duration_f32(STEP).ceil() as u32 {
+        app.update();
+    }
+}
@@ -0,0 +1,360 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use avian3d::prelude::{RayCaster, RayHits};
+use bevy::{
+    light::NotShadowCaster,
+    prelude::*,
+    scene::SceneInstanceReady,
+    window::{CursorGrabMode, CursorOptions, PrimaryWindow},
+};
+use puppeteer::puppet_rig::PuppetRig;
+
+use crate::{
+    GameState, PauseState,
+    building::placement::BuildMode,
+    game_resources::ResourceKind,
+    harvestable::Harvestable,
+    health::{DamageEvent, DamageKind},
+    input::{Action, InputActions},
+    player::{HIT_REACH, PLAYER_DAMAGE, Player, PlayerHit},
+};
+
+/// Length of a whole swing, the next one can start once it is over.
+pub const SWING_DURATION: Duration = Duration::from_millis(500);
+
+/// Time into a swing at which the tool strikes.
+pub const IMPACT_TIME: Duration = Duration::from_millis(200);
+
+/// Rotation of the tool at the moment of impact, tilted forward and down.
+const STRIKE_ANGLE: f32 = -1.3;
+
+/// Where the tool is held relative to the camera.
+const REST_POSITION: Vec3 = Vec3::new(0.3, -0.28, -0.5);
+
+const HAMMER_MODEL_PATH: &str = "tools/hammer.gltf";
+
+pub struct ToolPlugin;
+
+impl Plugin for ToolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ToolAssets>()
+            .add_systems(
+                Update,
+                (switch_tool, swing_tool)
+                    .chain()
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                Update,
+                (attach_viewmodel, update_viewmodel, animate_viewmodel)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tool {
+    #[default]
+    Axe,
+    Pickaxe,
+    /// Held while building.
+    Hammer,
+}
+
+/// How well a [`Tool`] works on what it hits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolModifiers {
+    /// Multiplied with the [`PLAYER_DAMAGE`].
+    pub damage: f32,
+    /// Multiplied with the yield of a resource node.
+    pub yield_multiplier: u32,
+}
+
+impl Tool {
+    pub fn next(self) -> Self {
+        match self {
+            Tool::Axe => Tool::Pickaxe,
+            Tool::Pickaxe => Tool::Hammer,
+            Tool::Hammer => Tool::Axe,
+        }
+    }
+
+    /// Resource this tool is made to harvest.
+    pub fn harvests(self) -> Option<ResourceKind> {
+        match self {
+            Tool::Axe => Some(ResourceKind::Wood),
+            Tool::Pickaxe => Some(ResourceKind::Stone),
+            Tool::Hammer => None,
+        }
+    }
+
+    pub fn damage_kind(self) -> DamageKind {
+        match self {
+            Tool::Axe => DamageKind::Slash,
+            Tool::Pickaxe => DamageKind::Pierce,
+            Tool::Hammer => DamageKind::Blunt,
+        }
+    }
+
+    /// Modifiers of a hit on a resource node of `kind`, or on anything else, e.g. an enemy,
+    /// with `None`.
+    pub fn modifiers(self, kind: Option<ResourceKind>) -> ToolModifiers {
+        match (self, kind) {
+            // The wrong tool barely dents a node and yields nothing
+            (_, Some(kind)) if self.harvests() != Some(kind) => ToolModifiers {
+                damage: 0.25,
+                yield_multiplier: 0,
+            },
+            (Tool::Hammer, None) => ToolModifiers {
+                damage: 0.5,
+                yield_multiplier: 1,
+            },
+            _ => ToolModifiers {
+                damage: 1.0,
+                yield_multiplier: 1,
+            },
+        }
+    }
+}
+
+/// The tool the player holds and its current swing.
+#[derive(Component, Debug, Default)]
+pub struct HeldTool {
+    pub tool: Tool,
+    /// Tool to take again once build mode ends.
+    before_building: Option<Tool>,
+    swing: Option<Timer>,
+    struck: bool,
+}
+
+impl HeldTool {
+    /// Progress through the current swing from 0 to 1, `None` while idle.
+    pub fn swing_progress(&self) -> Option<f32> {
+        self.swing.as_ref().map(Timer::fraction)
+    }
+}
+
+/// Model of the held tool in front of the camera.
+#[derive(Component, Debug, Default)]
+struct ToolViewmodel {
+    shown: Option<Tool>,
+}
+
+#[derive(Resource)]
+struct ToolAssets {
+    handle: Handle<Mesh>,
+    axe_head: Handle<Mesh>,
+    pickaxe_head: Handle<Mesh>,
+    hammer: Handle<Scene>,
+    wood: Handle<StandardMaterial>,
+    metal: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ToolAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let handle = meshes.add(Cylinder::new(0.02, 0.45));
+        let axe_head = meshes.add(Cuboid::new(0.02, 0.1, 0.14));
+        let pickaxe_head = meshes.add(Cuboid::new(0.03, 0.03, 0.32));
+        let hammer = world
+            .resource::<AssetServer>()
+            .load(GltfAssetLabel::Scene(0).from_asset(HAMMER_MODEL_PATH));
+
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        Self {
+            handle,
+            axe_head,
+            pickaxe_head,
+            hammer,
+            wood: materials.add(StandardMaterial {
+                base_color: Srgba::hex("7a5230").unwrap().into(),
+                perceptual_roughness: 1.0,
+                ..default()
+            }),
+            metal: materials.add(StandardMaterial {
+                base_color: Srgba::hex("9a9ea3").unwrap().into(),
+                metallic: 0.8,
+                perceptual_roughness: 0.4,
+                ..default()
+            }),
+        }
+    }
+}
+
+/// Cycles the tools, and takes the hammer while building.
+fn switch_tool(
+    mut held: Single<&mut HeldTool>,
+    actions: Res<InputActions>,
+    build_mode: Res<BuildMode>,
+) {
+    if build_mode.is_changed() {
+        if build_mode.active && held.before_building.is_none() {
+            held.before_building = Some(held.tool);
+            held.tool = Tool::Hammer;
+        } else if !build_mode.active
+            && let Some(tool) = held.before_building.take()
+        {
+            held.tool = tool;
+        }
+    }
+    if actions.just_pressed(Action::NextTool) && !build_mode.active && held.swing.is_none() {
+        held.tool = held.tool.next();
+    }
+}
+
+/// Starts a swing while hit is held, and hits the targeted entity at its impact.
+fn swing_tool(
+    mut commands: Commands,
+    player: Single<(Entity, &mut HeldTool), With<Player>>,
+    rays: Query<(&RayCaster, &RayHits), With<PuppetRig>>,
+    ancestors: Query<&ChildOf>,
+    harvestables: Query<&Harvestable>,
+    actions: Res<InputActions>,
+    window: Single<&CursorOptions, With<PrimaryWindow>>,
+    time: Res<Time>,
+) {
+    let (player, mut held) = player.into_inner();
+    let held = &mut *held;
+
+    if let Some(swing) = &mut held.swing {
+        swing.tick(time.delta());
+        if !held.struck && swing.elapsed() >= IMPACT_TIME {
+            held.struck = true;
+            let hit = rays
+                .iter()
+                .filter_map(|(caster, hits)| Some((caster, hits.first()?)))
+                .find(|(_, hit)| hit.distance <= HIT_REACH);
+            if let Some((caster, hit)) = hit {
+                // Resource nodes are hit on one of their colliders
+                let kind = std::iter::once(hit.entity)
+                    .chain(ancestors.iter_ancestors(hit.entity))
+                    .find_map(|entity| harvestables.get(entity).ok())
+                    .map(|harvestable| harvestable.kind);
+                commands.trigger(PlayerHit {
+                    entity: hit.entity,
+                    hit: *hit,
+                    point: caster.global_origin() + caster.global_direction() * hit.distance,
+                });
+                commands.trigger(DamageEvent {
+                    entity: hit.entity,
+                    amount: PLAYER_DAMAGE * held.tool.modifiers(kind).damage,
+                    source: player,
+                    kind: held.tool.damage_kind(),
+                });
+            }
+        }
+        if swing.is_finished() {
+            held.swing = None;
+        }
+    }
+
+    // Clicks on open UI don't swing
+    if held.swing.is_none()
+        && actions.pressed(Action::Hit)
+        && window.grab_mode != CursorGrabMode::None
+    {
+        held.swing = Some(Timer::new(SWING_DURATION, TimerMode::Once));
+        held.struck = false;
+    }
+}
+
+fn attach_viewmodel(mut commands: Commands, cameras: Query<Entity, Added<PuppetRig>>) {
+    for camera in cameras.iter() {
+        commands.spawn((
+            Name::new("Tool"),
+            ToolViewmodel::default(),
+            Transform::from_translation(REST_POSITION),
+            Visibility::default(),
+            ChildOf(camera),
+        ));
+    }
+}
+
+/// Rebuilds the viewmodel whenever the held tool changes.
+fn update_viewmodel(
+    mut commands: Commands,
+    mut viewmodels: Query<(Entity, &mut ToolViewmodel)>,
+    held: Single<&HeldTool>,
+    assets: Res<ToolAssets>,
+) {
+    for (entity, mut viewmodel) in viewmodels.iter_mut() {
+        if viewmodel.shown == Some(held.tool) {
+            continue;
+        }
+        viewmodel.shown = Some(held.tool);
+
+        let mut viewmodel = commands.entity(entity);
+        viewmodel.despawn_children();
+        let head = match held.tool {
+            Tool::Axe => (
+                assets.axe_head.clone(),
+                Transform::from_xyz(0.0, 0.18, -0.06),
+            ),
+            Tool::Pickaxe => (
+                assets.pickaxe_head.clone(),
+                Transform::from_xyz(0.0, 0.2, 0.0),
+            ),
+            Tool::Hammer => {
+                viewmodel.with_children(|parent| {
+                    // Scaled to the length of the other tools, with the head pointing forward
+                    parent
+                        .spawn((
+                            SceneRoot(assets.hammer.clone()),
+                            Transform::from_xyz(0.0, -0.225, 0.0)
+                                .with_rotation(Quat::from_rotation_y(FRAC_PI_2))
+                                .with_scale(Vec3::splat(0.55)),
+                        ))
+                        .observe(hide_viewmodel_shadows);
+                });
+                continue;
+            }
+        };
+        viewmodel.with_children(|parent| {
+            parent.spawn((
+                Mesh3d(assets.handle.clone()),
+                MeshMaterial3d(assets.wood.clone()),
+                NotShadowCaster,
+            ));
+            parent.spawn((
+                Mesh3d(head.0),
+                MeshMaterial3d(assets.metal.clone()),
+                head.1,
+                NotShadowCaster,
+            ));
+        });
+    }
+}
+
+/// The meshes of a model held in front of the camera would shadow the whole view.
+fn hide_viewmodel_shadows(
+    ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    children: Query<&Children>,
+    meshes: Query<(), With<Mesh3d>>,
+) {
+    for entity in children.iter_descendants(ready.event_target()) {
+        if meshes.contains(entity) {
+            commands.entity(entity).insert(NotShadowCaster);
+        }
+    }
+}
+
+/// Swings the viewmodel down to the impact and back up again.
+fn animate_viewmodel(
+    mut viewmodels: Query<&mut Transform, With<ToolViewmodel>>,
+    held: Single<&HeldTool>,
+) {
+    let impact = IMPACT_TIME.as_secs_f32() / SWING_DURATION.as_secs_f32();
+    let angle = held.swing_progress().map_or(0.0, |progress| {
+        if progress < impact {
+            // Accelerates into the strike
+            STRIKE_ANGLE * (progress / impact).powi(2)
+        } else {
+            STRIKE_ANGLE * (1.0 - (progress - impact) / (1.0 - impact))
+        }
+    });
+    for mut transform in viewmodels.iter_mut() {
+        transform.rotation = Quat::from_rotation_x(angle);
+    }
+}
@@ -0,0 +1,376 @@
+use std::time::Duration;
+
+use avian3d::prelude::{
+    Collider, CollisionEventsEnabled, CollisionStart, GravityScale, LinearVelocity, RigidBody,
+    Sensor, SpatialQuery, SpatialQueryFilter,
+};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    GameState,
+    enemy::Enemy,
+    health::{DamageEvent, DamageKind, Health},
+    world::CASTLE_SITE,
+};
+
+/// Speed of tower projectiles in units per second.
+pub const PROJECTILE_SPEED: f32 = 30.0;
+
+pub struct TowerPlugin;
+
+impl Plugin for TowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ProjectileAssets>()
+            .add_observer(add_projectile_visuals)
+            .add_systems(
+                Update,
+                (fire_towers, expire_projectiles).run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// Which enemy in range a [`Tower`] shoots at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum TargetingPolicy {
+    /// Closest to the tower.
+    #[default]
+    Nearest,
+    /// Closest to the castle.
+    First,
+    /// Most health left.
+    Strongest,
+    /// Least health left.
+    Weakest,
+}
+
+/// An enemy a [`Tower`] could shoot at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TargetCandidate {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub health: f32,
+}
+
+impl TargetingPolicy {
+    /// Picks the target among `candidates`, the first one wins ties.
+    pub fn select(
+        self,
+        tower: Vec3,
+        candidates: impl IntoIterator<Item = TargetCandidate>,
+    ) -> Option<Entity> {
+        let score = |candidate: &TargetCandidate| match self {
+            TargetingPolicy::Nearest => candidate.position.distance_squared(tower),
+            TargetingPolicy::First => candidate.position.xz().distance_squared(CASTLE_SITE),
+            TargetingPolicy::Strongest => -candidate.health,
+            TargetingPolicy::Weakest => candidate.health,
+        };
+        candidates
+            .into_iter()
+            .min_by(|a, b| score(a).total_cmp(&score(b)))
+            .map(|candidate| candidate.entity)
+    }
+}
+
+/// Shoots projectiles at enemies within `range`.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct Tower {
+    pub range: f32,
+    pub damage: f32,
+    pub targeting: TargetingPolicy,
+    /// Height above the tower's origin the projectiles start at.
+    pub muzzle_height: f32,
+    cooldown: Timer,
+}
+
+impl Tower {
+    /// `fire_rate` is in shots per second.
+    pub fn new(range: f32, fire_rate: f32, damage: f32, targeting: TargetingPolicy) -> Self {
+        let mut cooldown = Timer::from_seconds(fire_rate.recip(), TimerMode::Once);
+        // Ready to shoot right away
+        cooldown.finish();
+        Self {
+            range,
+            damage,
+            targeting,
+            muzzle_height: 0.0,
+            cooldown,
+        }
+    }
+
+    pub fn with_muzzle_height(mut self, muzzle_height: f32) -> Self {
+        self.muzzle_height = muzzle_height;
+        self
+    }
+
+    pub fn fire_rate(&self) -> f32 {
+        self.cooldown.duration().as_secs_f32().recip()
+    }
+}
+
+/// Damages the first enemy it touches and despawns on any contact.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    pub damage: f32,
+    /// Tower that fired the projectile.
+    pub source: Entity,
+    lifetime: Timer,
+    /// Set on the first contact, the despawn only happens once commands are applied.
+    spent: bool,
+}
+
+#[derive(Resource)]
+struct ProjectileAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for ProjectileAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Sphere::new(0.1).mesh().ico(1).unwrap());
+        let material = world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial {
+                base_color: Srgba::hex("3b3430").unwrap().into(),
+                ..default()
+            });
+        Self { mesh, material }
+    }
+}
+
+/// Projectiles are spawned without any visuals so towers can be tested headless.
+fn add_projectile_visuals(
+    add: On<Add, Projectile>,
+    mut commands: Commands,
+    assets: Res<ProjectileAssets>,
+) {
+    commands.entity(add.entity).insert((
+        Mesh3d(assets.mesh.clone()),
+        MeshMaterial3d(assets.material.clone()),
+    ));
+}
+
+fn fire_towers(
+    mut commands: Commands,
+    mut towers: Query<(Entity, &mut Tower, &GlobalTransform)>,
+    enemies: Query<(&GlobalTransform, &Health), With<Enemy>>,
+    spatial_query: SpatialQuery,
+    time: Res<Time>,
+) {
+    for (entity, mut tower, transform) in towers.iter_mut() {
+        tower.cooldown.tick(time.delta());
+        if !tower.cooldown.is_finished() {
+            continue;
+        }
+
+        let origin = transform.translation();
+        let candidates = spatial_query
+            .shape_intersections(
+                &Collider::sphere(tower.range),
+                origin,
+                Quat::IDENTITY,
+                &SpatialQueryFilter::default(),
+            )
+            .into_iter()
+            .filter_map(|collider| {
+                let (enemy_transform, health) = enemies.get(collider).ok()?;
+                (!health.is_dead()).then(|| TargetCandidate {
+                    entity: collider,
+                    position: enemy_transform.translation(),
+                    health: health.current,
+                })
+            });
+        let Some(target) = tower.targeting.select(origin, candidates) else {
+            continue;
+        };
+        let Ok((target_transform, _)) = enemies.get(target) else {
+            continue;
+        };
+
+        let muzzle = origin + Vec3::Y * tower.muzzle_height;
+        let Ok(direction) = Dir3::new(target_transform.translation() - muzzle) else {
+            continue;
+        };
+        tower.cooldown.reset();
+        commands
+            .spawn((
+                Name::new("Projectile"),
+                Projectile {
+                    damage: tower.damage,
+                    source: entity,
+                    // Long enough to cross the whole range
+                    lifetime: Timer::new(
+                        Duration::from_secs_f32(tower.range * 2.0 / PROJECTILE_SPEED),
+                        TimerMode::Once,
+                    ),
+                    spent: false,
+                },
+                Transform::from_translation(muzzle).looking_to(direction, Vec3::Y),
+                RigidBody::Dynamic,
+                GravityScale(0.0),
+                LinearVelocity(direction * PROJECTILE_SPEED),
+                Collider::sphere(0.1),
+                Sensor,
+                CollisionEventsEnabled,
+                DespawnOnExit(GameState::InGame),
+            ))
+            .observe(hit_target);
+    }
+}
+
+fn hit_target(
+    collision: On<CollisionStart>,
+    mut commands: Commands,
+    mut projectiles: Query<&mut Projectile>,
+    enemies: Query<(), With<Enemy>>,
+    towers: Query<(), With<Tower>>,
+) {
+    let other = collision.body2.unwrap_or(collision.collider2);
+    // Passes through towers and other projectiles
+    if towers.contains(other) || projectiles.contains(other) {
+        return;
+    }
+    // Several contacts can start in the same step, only the first one counts
+    let Ok(mut projectile) = projectiles.get_mut(collision.collider1) else {
+        return;
+    };
+    if projectile.spent {
+        return;
+    }
+    projectile.spent = true;
+
+    if enemies.contains(other) {
+        commands.trigger(DamageEvent {
+            entity: other,
+            amount: projectile.damage,
+            source: projectile.source,
+            kind: DamageKind::Pierce,
+        });
+    }
+    commands.entity(collision.collider1).try_despawn();
+}
+
+fn expire_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Projectile)>,
+    time: Res<Time>,
+) {
+    for (entity, mut projectile) in projectiles.iter_mut() {
+        projectile.lifetime.tick(time.delta());
+        if projectile.lifetime.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use avian3d::PhysicsPlugins;
+
+    use super::*;
+    use crate::{
+        health::HealthPlugin,
+        testing::{advance, headless_app},
+    };
+
+    fn candidates(world: &mut World, positions_and_health: &[(Vec3, f32)]) -> Vec<TargetCandidate> {
+        positions_and_health
+            .iter()
+            .map(|&(position, health)| TargetCandidate {
+                entity: world.spawn_empty().id(),
+                position,
+                health,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn policies_pick_their_target() {
+        let mut world = World::new();
+        let tower = Vec3::new(20.0, 0.0, 0.0);
+        let castle = CASTLE_SITE.extend(0.0).xzy();
+        let [near_tower, near_castle, strong, weak]: [TargetCandidate; 4] = candidates(
+            &mut world,
+            &[
+                (tower + Vec3::X, 50.0),
+                (castle + Vec3::Z, 40.0),
+                (tower + Vec3::X * 8.0, 90.0),
+                (tower - Vec3::X * 8.0, 10.0),
+            ],
+        )
+        .try_into()
+        .unwrap();
+        let all = [near_tower, near_castle, strong, weak];
+
+        assert_eq!(
+            TargetingPolicy::Nearest.select(tower, all),
+            Some(near_tower.entity)
+        );
+        assert_eq!(
+            TargetingPolicy::First.select(tower, all),
+            Some(near_castle.entity)
+        );
+        assert_eq!(
+            TargetingPolicy::Strongest.select(tower, all),
+            Some(strong.entity)
+        );
+        assert_eq!(
+            TargetingPolicy::Weakest.select(tower, all),
+            Some(weak.entity)
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_first_candidate() {
+        let mut world = World::new();
+        let tied = candidates(&mut world, &[(Vec3::X, 50.0), (-Vec3::X, 50.0)]);
+        for policy in [
+            TargetingPolicy::Nearest,
+            TargetingPolicy::Strongest,
+            TargetingPolicy::Weakest,
+        ] {
+            assert_eq!(
+                policy.select(Vec3::ZERO, tied.clone()),
+                Some(tied[0].entity)
+            );
+        }
+    }
+
+    #[test]
+    fn no_candidates_no_target() {
+        assert_eq!(TargetingPolicy::Nearest.select(Vec3::ZERO, []), None);
+    }
+
+    #[test]
+    fn towers_shoot_enemies_in_range() {
+        let mut app = headless_app();
+        app.insert_state(GameState::InGame).add_plugins((
+            TransformPlugin,
+            PhysicsPlugins::default(),
+            HealthPlugin,
+            TowerPlugin,
+        ));
+        app.world_mut().spawn((
+            Tower::new(10.0, 1.0, 5.0, TargetingPolicy::Nearest),
+            Transform::default(),
+        ));
+        let in_range = app
+            .world_mut()
+            .spawn((Enemy { wave: 1 }, Transform::from_xyz(6.0, 0.0, 0.0)))
+            .id();
+        let out_of_range = app
+            .world_mut()
+            .spawn((Enemy { wave: 1 }, Transform::from_xyz(-15.0, 0.0, 0.0)))
+            .id();
+
+        advance(&mut app, Duration::from_millis(1500));
+
+        let health = |app: &App, entity: Entity| *app.world().get::<Health>(entity).unwrap();
+        let hit = health(&app, in_range);
+        assert!(hit.current < hit.max, "enemy in range was not hit");
+        let missed = health(&app, out_of_range);
+        assert_eq!(missed.current, missed.max);
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::{
+    input_focus::{
+        InputFocus, InputFocusVisible,
+        tab_navigation::{NavAction, TabNavigation},
+    },
+    prelude::*,
+    ui::{Checked, InteractionDisabled},
+    ui_widgets::{Activate, Button, Checkbox, SliderRange, SliderStep, SliderValue, ValueChange},
+};
+
+/// Moves the focus between the widgets of the open [`TabGroup`](bevy::input_focus::tab_navigation::TabGroup)s
+/// with the D-pad and uses the focused widget with the south button, like Tab and Enter do.
+pub(crate) fn gamepad_navigation(
+    mut commands: Commands,
+    gamepads: Query<&Gamepad>,
+    navigation: TabNavigation,
+    mut focus: ResMut<InputFocus>,
+    mut focus_visible: ResMut<InputFocusVisible>,
+    buttons: Query<Has<InteractionDisabled>, With<Button>>,
+    checkboxes: Query<(Has<Checked>, Has<InteractionDisabled>), With<Checkbox>>,
+    sliders: Query<(
+        &SliderValue,
+        &SliderRange,
+        &SliderStep,
+        Has<InteractionDisabled>,
+    )>,
+) {
+    let just_pressed = |button| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+
+    let nav_action = if just_pressed(GamepadButton::DPadDown) {
+        Some(NavAction::Next)
+    } else if just_pressed(GamepadButton::DPadUp) {
+        Some(NavAction::Previous)
+    } else {
+        None
+    };
+    if let Some(nav_action) = nav_action {
+        if let Ok(next) = navigation.navigate(&focus, nav_action) {
+            focus.set(next);
+            focus_visible.0 = true;
+        }
+        return;
+    }
+
+    let Some(focused) = focus.get() else {
+        return;
+    };
+    // Disabled widgets keep the focus but can't be used
+    if just_pressed(GamepadButton::South) {
+        if buttons.get(focused).is_ok_and(|disabled| !disabled) {
+            commands.trigger(Activate { entity: focused });
+        } else if let Ok((checked, false)) = checkboxes.get(focused) {
+            commands.trigger(ValueChange {
+                source: focused,
+                value: !checked,
+            });
+        }
+    }
+
+    let direction = just_pressed(GamepadButton::DPadRight) as i8 as f32
+        - just_pressed(GamepadButton::DPadLeft) as i8 as f32;
+    if direction != 0.0
+        && let Ok((value, range, step, false)) = sliders.get(focused)
+    {
+        commands.trigger(ValueChange {
+            source: focused,
+            value: range.clamp(value.0 + direction * step.0),
+        });
+    }
+}
@@ -8,12 +8,20 @@ use crate::{
     constants::fonts,
     game_resources::GameResources,
     ui::{PAPER_SLICER, SCROLL_SLICER, widgets::SlicedImage},
+    wave::WaveDirector,
 };
 
 #[derive(Component)]
 pub struct UpdateResource;
 
-pub(crate) fn spawn_banner(mut commands: Commands, game_resources: Res<GameResources>) {
+#[derive(Component)]
+pub struct UpdateDay;
+
+pub(crate) fn spawn_banner(
+    mut commands: Commands,
+    game_resources: Res<GameResources>,
+    wave_director: Res<WaveDirector>,
+) {
     let mut root = commands.spawn((
         Node {
             width: percent(100),
@@ -72,10 +80,11 @@ pub(crate) fn spawn_banner(mut commands: Commands, game_resources: Res<GameResou
         },
         ZIndex(100),
         children![(
-            Text::new("Day 1"),
+            Text::new(format!("Day {}", wave_director.day())),
             TextLayout::new_with_linebreak(LineBreak::NoWrap),
             ThemedText,
-            TextColor(Color::srgb_u8(130, 85, 45))
+            TextColor(Color::srgb_u8(130, 85, 45)),
+            UpdateDay
         ),],
     ));
 }
@@ -91,3 +100,15 @@ pub fn update_resources(
         text.0 = format!("{}", *resources);
     }
 }
+
+pub fn update_day(mut query: Query<&mut Text, With<UpdateDay>>, wave_director: Res<WaveDirector>) {
+    if !wave_director.is_changed() {
+        return;
+    }
+    let day = format!("Day {}", wave_director.day());
+    for mut text in query.iter_mut() {
+        if text.0 != day {
+            text.0 = day.clone();
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::{
+    ecs::spawn::SpawnWith,
     feathers::theme::ThemedText,
     input_focus::tab_navigation::TabGroup,
     prelude::*,
@@ -8,10 +9,13 @@ use bevy::{
 
 use crate::{
     GameState,
+    save::{LoadGame, latest_save},
     ui::widgets::{button, window},
 };
 
 pub(crate) fn spawn_main_menu(mut commands: Commands) {
+    let latest_save = latest_save();
+
     commands.spawn((Camera2d, DespawnOnExit(GameState::MainMenu)));
 
     let mut root = commands.spawn((
@@ -36,6 +40,37 @@ pub(crate) fn spawn_main_menu(mut commands: Commands) {
             ..default()
         },
         (
+            Spawn((
+                Node {
+                    min_width: px(80),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    margin: UiRect::bottom(px(4)),
+                    ..default()
+                },
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    let mut continue_button = parent.spawn(button(
+                        (),
+                        Spawn((
+                            Text::new("Continue"),
+                            ThemedText,
+                            TextColor(Color::srgb_u8(130, 85, 45)),
+                        )),
+                    ));
+                    match latest_save {
+                        Some(path) => {
+                            continue_button.observe(
+                                move |_activate: On<Activate>, mut commands: Commands| {
+                                    commands.trigger(LoadGame { path: path.clone() });
+                                },
+                            );
+                        }
+                        None => {
+                            continue_button.insert(InteractionDisabled);
+                        }
+                    }
+                }),
+            )),
             Spawn((
                 Node {
                     min_width: px(80),
@@ -47,7 +82,7 @@ pub(crate) fn spawn_main_menu(mut commands: Commands) {
                 children![button(
                     observe(
                         |_activate: On<Activate>, mut next_state: ResMut<NextState<GameState>>| {
-                            next_state.set(GameState::InGame);
+                            next_state.set(GameState::NewGame);
                         },
                     ),
                     Spawn((
@@ -66,7 +101,11 @@ pub(crate) fn spawn_main_menu(mut commands: Commands) {
                     ..default()
                 },
                 children![button(
-                    InteractionDisabled,
+                    observe(
+                        |_activate: On<Activate>, mut next_state: ResMut<NextState<GameState>>| {
+                            next_state.set(GameState::Settings);
+                        },
+                    ),
                     Spawn((
                         Text::new("Settings"),
                         ThemedText,
@@ -1,16 +1,31 @@
 use bevy::{
     app::{App, Plugin, Update},
+    ecs::schedule::IntoScheduleConfigs,
     sprite::{BorderRect, SliceScaleMode, TextureSlicer},
-    state::state::OnEnter,
+    state::{condition::in_state, state::OnEnter},
 };
 
 use crate::{
-    GameState,
-    ui::{in_game_banner::update_resources, main_menu::spawn_main_menu, widgets::button_hover},
+    GameState, PauseState,
+    ui::{
+        gamepad_navigation::gamepad_navigation,
+        in_game_banner::{update_day, update_resources},
+        main_menu::spawn_main_menu,
+        new_game_menu::{focus_seed_input, spawn_new_game_menu, update_seed_input},
+        pause_menu::{spawn_pause_menu, toggle_pause},
+        research_window::{toggle_research_window, update_research_window},
+        settings_menu::{Rebinding, capture_rebinding, spawn_settings_menu, update_settings_menu},
+        widgets::{button_hover, update_checkbox_marks, update_slider_thumbs},
+    },
 };
 
+pub(crate) mod gamepad_navigation;
 mod in_game_banner;
 mod main_menu;
+mod new_game_menu;
+mod pause_menu;
+mod research_window;
+mod settings_menu;
 pub mod widgets;
 
 pub const SCROLL_SLICER: TextureSlicer = TextureSlicer {
@@ -53,8 +68,35 @@ pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+        app.init_resource::<Rebinding>()
+            .add_systems(OnEnter(GameState::MainMenu), spawn_main_menu)
+            .add_systems(OnEnter(GameState::NewGame), spawn_new_game_menu)
+            .add_systems(OnEnter(GameState::Settings), spawn_settings_menu)
             .add_systems(OnEnter(GameState::InGame), in_game_banner::spawn_banner)
-            .add_systems(Update, (button_hover, update_resources));
+            .add_systems(OnEnter(PauseState::Paused), spawn_pause_menu)
+            .add_systems(
+                Update,
+                (
+                    button_hover,
+                    gamepad_navigation,
+                    update_slider_thumbs,
+                    update_checkbox_marks,
+                    update_resources,
+                    update_day,
+                    (focus_seed_input, update_seed_input).chain(),
+                    (capture_rebinding, update_settings_menu).chain(),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Sees a rebinding before Escape cancels it
+                    toggle_pause.before(capture_rebinding),
+                    toggle_research_window.run_if(in_state(PauseState::Running)),
+                    update_research_window,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            );
     }
 }
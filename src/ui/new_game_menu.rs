@@ -0,0 +1,207 @@
+use bevy::{
+    feathers::{font_styles::InheritableFont, handle_or_path::HandleOrPath, theme::ThemedText},
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    input_focus::{
+        FocusedInput, InputFocus,
+        tab_navigation::{TabGroup, TabIndex},
+    },
+    prelude::*,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    GameState,
+    constants::fonts,
+    ui::{
+        PAPER_SLICER,
+        widgets::{SlicedImage, button, window},
+    },
+    world::WorldSeed,
+};
+
+/// Longest seed that can be typed.
+const MAX_SEED_LENGTH: usize = 20;
+
+/// Text field the seed of the new world is typed into.
+#[derive(Component, Debug, Default)]
+pub(crate) struct SeedInput {
+    text: String,
+}
+
+pub(crate) fn spawn_new_game_menu(mut commands: Commands) {
+    commands.spawn((Camera2d, DespawnOnExit(GameState::NewGame)));
+
+    commands.spawn((
+        Node {
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        DespawnOnExit(GameState::NewGame),
+        TabGroup::default(),
+        children![window(
+            "New Game",
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            (
+                Spawn((
+                    Text::new("Seed"),
+                    ThemedText,
+                    TextFont::from_font_size(10.0),
+                    TextColor(Color::srgb_u8(130, 85, 45)),
+                )),
+                Spawn((
+                    Node {
+                        width: px(120),
+                        min_height: px(16),
+                        align_items: AlignItems::Center,
+                        padding: UiRect::horizontal(px(5)).with_bottom(px(2)),
+                        margin: UiRect::bottom(px(6)),
+                        ..default()
+                    },
+                    SeedInput {
+                        text: WorldSeed::random().to_string(),
+                    },
+                    TabIndex(0),
+                    observe(type_seed),
+                    InheritableFont {
+                        font: HandleOrPath::Path(fonts::REGULAR.to_owned()),
+                        font_size: 10.0,
+                    },
+                    SlicedImage {
+                        image: HandleOrPath::Path("textures/ui/paper.png".to_string()),
+                        slicer: PAPER_SLICER,
+                    },
+                    children![(
+                        Text::default(),
+                        ThemedText,
+                        TextColor(Color::srgb_u8(130, 85, 45)),
+                    )],
+                )),
+                Spawn((
+                    Node {
+                        column_gap: px(4),
+                        ..default()
+                    },
+                    children![
+                        text_button(
+                            "Random",
+                            observe(
+                                |_activate: On<Activate>, mut input: Single<&mut SeedInput>| {
+                                    input.text = WorldSeed::random().to_string();
+                                }
+                            ),
+                        ),
+                        text_button(
+                            "Start",
+                            observe(
+                                |_activate: On<Activate>,
+                                 mut commands: Commands,
+                                 input: Single<&SeedInput>,
+                                 mut next_state: ResMut<NextState<GameState>>| {
+                                    let seed = if input.text.trim().is_empty() {
+                                        WorldSeed::random()
+                                    } else {
+                                        WorldSeed::from_text(&input.text)
+                                    };
+                                    commands.insert_resource(seed);
+                                    next_state.set(GameState::InGame);
+                                }
+                            ),
+                        ),
+                        text_button(
+                            "Back",
+                            observe(
+                                |_activate: On<Activate>,
+                                 mut next_state: ResMut<NextState<GameState>>| {
+                                    next_state.set(GameState::MainMenu);
+                                }
+                            ),
+                        ),
+                    ],
+                )),
+            ),
+        )],
+    ));
+}
+
+/// Focuses the seed field when the dialog opens, so the seed can be typed right away.
+pub(crate) fn focus_seed_input(
+    inputs: Query<Entity, Added<SeedInput>>,
+    mut focus: ResMut<InputFocus>,
+) {
+    if let Some(entity) = inputs.iter().next() {
+        focus.set(entity);
+    }
+}
+
+fn type_seed(mut typed: On<FocusedInput<KeyboardInput>>, mut inputs: Query<&mut SeedInput>) {
+    let Ok(mut input) = inputs.get_mut(typed.event_target()) else {
+        return;
+    };
+    if typed.input.state != ButtonState::Pressed {
+        return;
+    }
+    match &typed.input.logical_key {
+        Key::Backspace => {
+            input.text.pop();
+        }
+        Key::Character(characters) => {
+            for character in characters
+                .chars()
+                .filter(|character| !character.is_control())
+            {
+                if input.text.chars().count() < MAX_SEED_LENGTH {
+                    input.text.push(character);
+                }
+            }
+        }
+        // Leaves Tab and Enter to the focus navigation
+        _ => return,
+    }
+    typed.propagate(false);
+}
+
+/// Shows the typed seed, with a caret while the field is focused.
+pub(crate) fn update_seed_input(
+    inputs: Query<(Entity, &SeedInput, &Children)>,
+    mut texts: Query<&mut Text>,
+    focus: Res<InputFocus>,
+) {
+    for (entity, input, children) in inputs.iter() {
+        let caret = if focus.get() == Some(entity) { "_" } else { "" };
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                let shown = format!("{}{caret}", input.text);
+                if text.0 != shown {
+                    text.0 = shown;
+                }
+            }
+        }
+    }
+}
+
+fn text_button<B: Bundle>(label: &'static str, overrides: B) -> impl Bundle {
+    (
+        Node {
+            min_width: px(50),
+            ..default()
+        },
+        children![button(
+            overrides,
+            Spawn((
+                Text::new(label),
+                ThemedText,
+                TextColor(Color::srgb_u8(130, 85, 45)),
+            ))
+        )],
+    )
+}
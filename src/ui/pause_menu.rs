@@ -0,0 +1,127 @@
+use bevy::{
+    feathers::theme::ThemedText,
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    GameState, PauseState,
+    input::{Action, InputActions},
+    save::{SAVE_PATH, SaveGame},
+    ui::{
+        settings_menu::{CloseSettings, Rebinding, SettingsMenu},
+        widgets::{button, window},
+    },
+};
+
+/// Root of the pause menu, which also hosts the [`SettingsMenu`] so the game keeps running
+/// in the background.
+#[derive(Component)]
+pub struct PauseMenu;
+
+/// Pauses and resumes the game. Ignored while the pressed key is being bound to an action.
+pub(crate) fn toggle_pause(
+    actions: Res<InputActions>,
+    rebinding: Res<Rebinding>,
+    state: Res<State<PauseState>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if !actions.just_pressed(Action::Pause) || rebinding.is_waiting() {
+        return;
+    }
+    next_state.set(match state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+pub(crate) fn spawn_pause_menu(mut commands: Commands) {
+    let mut root = commands.spawn((
+        Node {
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+        PauseMenu,
+        TabGroup::default(),
+        DespawnOnExit(PauseState::Paused),
+    ));
+    let entity = root.id();
+    root.with_child(pause_window(entity)).observe(
+        |close: On<CloseSettings>, mut commands: Commands| {
+            commands
+                .entity(close.entity)
+                .remove::<SettingsMenu>()
+                .despawn_children()
+                .with_child(pause_window(close.entity));
+        },
+    );
+}
+
+fn pause_window(menu: Entity) -> impl Bundle {
+    window(
+        "Paused",
+        Node {
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        (
+            Spawn(menu_button(
+                "Resume",
+                observe(
+                    |_activate: On<Activate>, mut next_state: ResMut<NextState<PauseState>>| {
+                        next_state.set(PauseState::Running);
+                    },
+                ),
+            )),
+            Spawn(menu_button(
+                "Settings",
+                observe(move |_activate: On<Activate>, mut commands: Commands| {
+                    commands.entity(menu).insert(SettingsMenu::default());
+                }),
+            )),
+            Spawn(menu_button(
+                "Save Game",
+                observe(|_activate: On<Activate>, mut commands: Commands| {
+                    commands.trigger(SaveGame {
+                        path: SAVE_PATH.into(),
+                    });
+                }),
+            )),
+            Spawn(menu_button(
+                "Quit to Menu",
+                observe(
+                    |_activate: On<Activate>, mut next_state: ResMut<NextState<GameState>>| {
+                        next_state.set(GameState::MainMenu);
+                    },
+                ),
+            )),
+        ),
+    )
+}
+
+fn menu_button<B: Bundle>(label: &'static str, overrides: B) -> impl Bundle {
+    (
+        Node {
+            min_width: px(80),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            margin: UiRect::bottom(px(4)),
+            ..default()
+        },
+        children![button(
+            overrides,
+            Spawn((
+                Text::new(label),
+                ThemedText,
+                TextColor(Color::srgb_u8(130, 85, 45)),
+            ))
+        )],
+    )
+}
@@ -0,0 +1,178 @@
+use bevy::{
+    ecs::spawn::SpawnWith,
+    feathers::theme::ThemedText,
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+    ui::InteractionDisabled,
+    ui_widgets::{Activate, observe},
+};
+
+use crate::{
+    GameState,
+    game_resources::GameResources,
+    input::{Action, InputActions},
+    pause::FreesCursor,
+    research::{Research, TechTree},
+    ui::widgets::{button, window},
+};
+
+/// Root of the open research window.
+#[derive(Component)]
+pub struct ResearchWindow;
+
+/// Opens and closes the research window.
+pub(crate) fn toggle_research_window(
+    mut commands: Commands,
+    research_window: Query<Entity, With<ResearchWindow>>,
+    actions: Res<InputActions>,
+) {
+    if !actions.just_pressed(Action::OpenResearch) {
+        return;
+    }
+
+    if let Ok(entity) = research_window.single() {
+        commands.entity(entity).despawn();
+        return;
+    }
+
+    commands.spawn((
+        Node {
+            width: percent(100),
+            height: percent(100),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ResearchWindow,
+        FreesCursor,
+        TabGroup::default(),
+        DespawnOnExit(GameState::InGame),
+    ));
+}
+
+/// Research button of the tech tree node with this id.
+#[derive(Component)]
+struct ResearchButton(String);
+
+/// Text showing the name and cost of the tech tree node with this id.
+#[derive(Component)]
+struct ResearchLabel(String);
+
+/// Label of a tech tree node and whether it can be researched right now.
+fn research_option(
+    tech_tree: &TechTree,
+    game_resources: &GameResources,
+    id: &str,
+) -> (String, bool) {
+    let Some(node) = tech_tree.get(id) else {
+        return (String::new(), false);
+    };
+    let label = if tech_tree.is_researched(&node.id) {
+        format!("{} (researched)", node.name)
+    } else {
+        format!("{} - {}", node.name, node.cost)
+    };
+    let available = tech_tree.available(&node.id).is_ok() && game_resources.can_afford(&node.cost);
+    (label, available)
+}
+
+/// Builds the window content when it opens, then keeps the labels and disabled buttons in sync
+/// with research and affordability.
+pub(crate) fn update_research_window(
+    mut commands: Commands,
+    research_window: Query<(Entity, Ref<ResearchWindow>)>,
+    buttons: Query<(Entity, &ResearchButton, Has<InteractionDisabled>)>,
+    mut labels: Query<(&ResearchLabel, &mut Text)>,
+    tech_tree: Res<TechTree>,
+    game_resources: Res<GameResources>,
+) {
+    let Ok((entity, marker)) = research_window.single() else {
+        return;
+    };
+    if marker.is_added() {
+        spawn_research_options(&mut commands, entity, &tech_tree, &game_resources);
+        return;
+    }
+    if !tech_tree.is_changed() && !game_resources.is_changed() {
+        return;
+    }
+
+    for (button, ResearchButton(id), disabled) in buttons.iter() {
+        let (_, available) = research_option(&tech_tree, &game_resources, id);
+        if available && disabled {
+            commands.entity(button).remove::<InteractionDisabled>();
+        } else if !available && !disabled {
+            commands.entity(button).insert(InteractionDisabled);
+        }
+    }
+    for (ResearchLabel(id), mut text) in labels.iter_mut() {
+        let (label, _) = research_option(&tech_tree, &game_resources, id);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+fn spawn_research_options(
+    commands: &mut Commands,
+    entity: Entity,
+    tech_tree: &TechTree,
+    game_resources: &GameResources,
+) {
+    let nodes: Vec<_> = tech_tree
+        .nodes()
+        .iter()
+        .map(|node| {
+            let (label, available) = research_option(tech_tree, game_resources, &node.id);
+            (node.id.clone(), label, node.description.clone(), available)
+        })
+        .collect();
+
+    commands.entity(entity).with_child(window(
+        "Research",
+        Node {
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        SpawnWith(move |parent: &mut ChildSpawner| {
+            for (id, label, description, available) in nodes {
+                parent
+                    .spawn(Node {
+                        min_width: px(160),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::bottom(px(4)),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        let research = id.clone();
+                        let mut research_button = row.spawn((
+                            button(
+                                observe(move |_activate: On<Activate>, mut commands: Commands| {
+                                    commands.trigger(Research {
+                                        id: research.clone(),
+                                    });
+                                }),
+                                Spawn((
+                                    Text::new(label),
+                                    ResearchLabel(id.clone()),
+                                    ThemedText,
+                                    TextColor(Color::srgb_u8(130, 85, 45)),
+                                )),
+                            ),
+                            ResearchButton(id),
+                        ));
+                        if !available {
+                            research_button.insert(InteractionDisabled);
+                        }
+                        row.spawn((
+                            Text::new(description),
+                            ThemedText,
+                            TextFont::from_font_size(10.0),
+                            TextColor(Color::srgb_u8(130, 85, 45)),
+                        ));
+                    });
+            }
+        }),
+    ));
+}
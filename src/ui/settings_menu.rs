@@ -0,0 +1,417 @@
+use bevy::{
+    ecs::spawn::SpawnWith,
+    feathers::theme::ThemedText,
+    input_focus::tab_navigation::TabGroup,
+    prelude::*,
+    ui::Checked,
+    ui_widgets::{Activate, SliderValue, ValueChange, observe},
+};
+
+use crate::{
+    GameState,
+    input::{Action, Binding, InputBindings},
+    settings::Settings,
+    ui::widgets::{button, checkbox, slider, window},
+};
+
+/// Root of a settings menu, showing this page. Changing it rebuilds the menu.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum SettingsMenu {
+    #[default]
+    General,
+    Controls,
+}
+
+/// Triggered on a [`SettingsMenu`] when it is left with its Back button.
+#[derive(EntityEvent, Debug, Clone, Copy)]
+pub(crate) struct CloseSettings {
+    pub entity: Entity,
+}
+
+/// Action waiting for the next key or button to be bound to it.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct Rebinding(Option<Action>);
+
+impl Rebinding {
+    pub(crate) fn is_waiting(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+pub(crate) fn spawn_settings_menu(mut commands: Commands) {
+    commands.spawn((Camera2d, DespawnOnExit(GameState::Settings)));
+
+    commands
+        .spawn((
+            Node {
+                width: percent(100),
+                height: percent(100),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            SettingsMenu::default(),
+            DespawnOnExit(GameState::Settings),
+            TabGroup::default(),
+        ))
+        .observe(
+            |_close: On<CloseSettings>, mut next_state: ResMut<NextState<GameState>>| {
+                next_state.set(GameState::MainMenu);
+            },
+        );
+}
+
+/// Rebuilds the menu when its page changes, and the controls whenever a binding changes.
+/// The general page keeps its widgets so sliders aren't interrupted while dragged.
+pub(crate) fn update_settings_menu(
+    mut commands: Commands,
+    menus: Query<(Entity, Ref<SettingsMenu>)>,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    for (entity, page) in menus.iter() {
+        let bindings_changed = settings.is_changed() || rebinding.is_changed();
+        if !page.is_changed() && !(*page == SettingsMenu::Controls && bindings_changed) {
+            continue;
+        }
+
+        let mut menu = commands.entity(entity);
+        menu.despawn_children();
+        match *page {
+            SettingsMenu::General => {
+                menu.with_child(general_page(entity, settings.clone()));
+            }
+            SettingsMenu::Controls => {
+                menu.with_child(controls_page(
+                    entity,
+                    settings.bindings.clone(),
+                    rebinding.0,
+                ));
+            }
+        }
+    }
+}
+
+/// Binds the next pressed key, mouse or gamepad button to the action waiting in [`Rebinding`].
+/// Escape cancels.
+pub(crate) fn capture_rebinding(
+    mut rebinding: ResMut<Rebinding>,
+    mut settings: ResMut<Settings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+) {
+    // Skips the key press that activated the binding's button
+    let Some(action) = rebinding.0.filter(|_| !rebinding.is_changed()) else {
+        return;
+    };
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    let binding = keys
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse_buttons
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepads
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+                .map(|button| Binding::Gamepad(*button))
+        });
+    if let Some(binding) = binding {
+        settings.bindings.rebind(action, binding);
+        rebinding.0 = None;
+    }
+}
+
+fn general_page(menu: Entity, settings: Settings) -> impl Bundle {
+    window(
+        "Settings",
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        (
+            Spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: px(4),
+                    margin: UiRect::bottom(px(6)),
+                    ..default()
+                },
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    parent.spawn(slider_row(
+                        "Mouse Sensitivity",
+                        settings.mouse_sensitivity,
+                        (0.1, 2.0, 0.05),
+                        |settings, value| settings.mouse_sensitivity = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Stick Look Speed",
+                        settings.stick_look_speed,
+                        (45.0, 360.0, 15.0),
+                        |settings, value| settings.stick_look_speed = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Stick Deadzone",
+                        settings.stick_deadzone,
+                        (0.0, 0.5, 0.05),
+                        |settings, value| settings.stick_deadzone = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "UI Scale",
+                        settings.ui_scale,
+                        (2.0, 6.0, 0.5),
+                        |settings, value| settings.ui_scale = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Master Volume",
+                        settings.master_volume,
+                        (0.0, 1.0, 0.05),
+                        |settings, value| settings.master_volume = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Music Volume",
+                        settings.music_volume,
+                        (0.0, 1.0, 0.05),
+                        |settings, value| settings.music_volume = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Effects Volume",
+                        settings.sfx_volume,
+                        (0.0, 1.0, 0.05),
+                        |settings, value| settings.sfx_volume = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Interface Volume",
+                        settings.ui_volume,
+                        (0.0, 1.0, 0.05),
+                        |settings, value| settings.ui_volume = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "Fog Distance",
+                        settings.fog_distance,
+                        (10.0, 60.0, 5.0),
+                        |settings, value| settings.fog_distance = value,
+                    ));
+                    parent.spawn(slider_row(
+                        "View Distance",
+                        settings.view_distance,
+                        (50.0, 200.0, 25.0),
+                        |settings, value| settings.view_distance = value,
+                    ));
+                    parent.spawn(checkbox_row(
+                        "Shadows",
+                        settings.shadows,
+                        |settings, value| settings.shadows = value,
+                    ));
+                    parent.spawn(checkbox_row("VSync", settings.vsync, |settings, value| {
+                        settings.vsync = value
+                    }));
+                }),
+            )),
+            Spawn((
+                Node {
+                    column_gap: px(4),
+                    ..default()
+                },
+                children![
+                    text_button(
+                        "Controls",
+                        observe(move |_activate: On<Activate>, mut commands: Commands| {
+                            commands.entity(menu).insert(SettingsMenu::Controls);
+                        }),
+                    ),
+                    text_button(
+                        "Back",
+                        observe(move |_activate: On<Activate>, mut commands: Commands| {
+                            commands.trigger(CloseSettings { entity: menu });
+                        }),
+                    ),
+                ],
+            )),
+        ),
+    )
+}
+
+fn controls_page(menu: Entity, bindings: InputBindings, rebinding: Option<Action>) -> impl Bundle {
+    window(
+        "Controls",
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        (
+            Spawn((
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: vec![
+                        GridTrack::auto(),
+                        GridTrack::px(56.0),
+                        GridTrack::auto(),
+                        GridTrack::px(56.0),
+                    ],
+                    align_items: AlignItems::Center,
+                    column_gap: px(6),
+                    row_gap: px(2),
+                    margin: UiRect::bottom(px(6)),
+                    ..default()
+                },
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    for action in Action::all() {
+                        let label = if rebinding == Some(action) {
+                            "Press a key".to_owned()
+                        } else {
+                            bindings
+                                .get(action)
+                                .iter()
+                                .map(Binding::to_string)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        };
+                        parent.spawn((
+                            Text::new(action.label()),
+                            ThemedText,
+                            TextFont::from_font_size(10.0),
+                            TextColor(Color::srgb_u8(130, 85, 45)),
+                        ));
+                        parent.spawn(button(
+                            observe(
+                                move |_activate: On<Activate>, mut rebinding: ResMut<Rebinding>| {
+                                    rebinding.0 = Some(action);
+                                },
+                            ),
+                            Spawn((
+                                Text::new(label),
+                                ThemedText,
+                                TextFont::from_font_size(10.0),
+                                TextColor(Color::srgb_u8(130, 85, 45)),
+                            )),
+                        ));
+                    }
+                }),
+            )),
+            Spawn((
+                Node {
+                    column_gap: px(4),
+                    ..default()
+                },
+                children![
+                    text_button(
+                        "Reset",
+                        observe(
+                            |_activate: On<Activate>,
+                             mut settings: ResMut<Settings>,
+                             mut rebinding: ResMut<Rebinding>| {
+                                settings.bindings = InputBindings::default();
+                                rebinding.0 = None;
+                            },
+                        ),
+                    ),
+                    text_button(
+                        "Back",
+                        observe(
+                            move |_activate: On<Activate>,
+                                  mut commands: Commands,
+                                  mut rebinding: ResMut<Rebinding>| {
+                                rebinding.0 = None;
+                                commands.entity(menu).insert(SettingsMenu::General);
+                            },
+                        ),
+                    ),
+                ],
+            )),
+        ),
+    )
+}
+
+fn text_button<B: Bundle>(label: &'static str, overrides: B) -> impl Bundle {
+    (
+        Node {
+            min_width: px(60),
+            ..default()
+        },
+        children![button(
+            overrides,
+            Spawn((
+                Text::new(label),
+                ThemedText,
+                TextColor(Color::srgb_u8(130, 85, 45)),
+            ))
+        )],
+    )
+}
+
+/// Label and slider, `range` is `(min, max, step)`.
+fn slider_row(
+    label: &'static str,
+    value: f32,
+    (min, max, step): (f32, f32, f32),
+    apply: fn(&mut Settings, f32),
+) -> impl Bundle {
+    (
+        Node {
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            column_gap: px(8),
+            ..default()
+        },
+        children![
+            (
+                Text::new(label),
+                ThemedText,
+                TextFont::from_font_size(10.0),
+                TextColor(Color::srgb_u8(130, 85, 45)),
+            ),
+            slider(
+                value,
+                min,
+                max,
+                step,
+                observe(
+                    move |change: On<ValueChange<f32>>,
+                          mut commands: Commands,
+                          mut settings: ResMut<Settings>| {
+                        commands
+                            .entity(change.source)
+                            .insert(SliderValue(change.value));
+                        apply(&mut settings, change.value);
+                    },
+                ),
+            ),
+        ],
+    )
+}
+
+fn checkbox_row(label: &'static str, checked: bool, apply: fn(&mut Settings, bool)) -> impl Bundle {
+    checkbox(
+        checked,
+        observe(
+            move |change: On<ValueChange<bool>>,
+                  mut commands: Commands,
+                  mut settings: ResMut<Settings>| {
+                if change.value {
+                    commands.entity(change.source).insert(Checked);
+                } else {
+                    commands.entity(change.source).remove::<Checked>();
+                }
+                apply(&mut settings, change.value);
+            },
+        ),
+        Spawn((
+            Text::new(label),
+            ThemedText,
+            TextFont::from_font_size(10.0),
+            TextColor(Color::srgb_u8(130, 85, 45)),
+        )),
+    )
+}
@@ -5,10 +5,11 @@ use bevy::{
     picking::hover::Hovered,
     prelude::*,
     ui::{InteractionDisabled, Pressed},
-    ui_widgets::Button,
+    ui_widgets::{Button, observe},
 };
 
 use crate::{
+    audio::click_sound,
     constants::fonts,
     ui::{PAPER_SLICER, PAPER_THICK_SLICER, widgets::SlicedImage},
 };
@@ -28,6 +29,7 @@ pub fn button<C: SpawnableList<ChildOf> + Send + Sync + 'static, B: Bundle>(
         Hovered::default(),
         //EntityCursor::System(bevy_window::SystemCursorIcon::Pointer),
         TabIndex(0),
+        observe(click_sound),
         overrides,
         children![(
             Node {
@@ -0,0 +1,97 @@
+use bevy::{
+    ecs::{lifecycle::HookContext, spawn::SpawnableList, world::DeferredWorld},
+    feathers::handle_or_path::HandleOrPath,
+    input_focus::tab_navigation::TabIndex,
+    picking::hover::Hovered,
+    prelude::*,
+    ui::Checked,
+    ui_widgets::Checkbox,
+};
+
+use crate::ui::{PAPER_THICK_SLICER, widgets::SlicedImage};
+
+/// Checkbox followed by its label. It is toggled by observing
+/// [`ValueChange<bool>`](bevy::ui_widgets::ValueChange) and inserting or removing [`Checked`].
+pub fn checkbox<C: SpawnableList<ChildOf> + Send + Sync + 'static, B: Bundle>(
+    checked: bool,
+    overrides: B,
+    label: C,
+) -> impl Bundle {
+    (
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: px(4),
+            ..Default::default()
+        },
+        Checkbox,
+        InitiallyChecked(checked),
+        Hovered::default(),
+        TabIndex(0),
+        overrides,
+        Children::spawn((
+            Spawn((
+                Node {
+                    width: px(12),
+                    height: px(12),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::bottom(px(2)),
+                    ..Default::default()
+                },
+                SlicedImage {
+                    image: HandleOrPath::Path("textures/ui/paper_thick.png".to_string()),
+                    slicer: PAPER_THICK_SLICER,
+                },
+                children![(
+                    Node {
+                        width: px(6),
+                        height: px(6),
+                        ..Default::default()
+                    },
+                    CheckMark,
+                    BackgroundColor(Color::srgb_u8(130, 85, 45)),
+                    Visibility::Hidden,
+                )],
+            )),
+            label,
+        )),
+    )
+}
+
+#[derive(Component)]
+pub struct CheckMark;
+
+/// Inserts [`Checked`] on spawn if requested, as bundles can't contain optional components.
+#[derive(Component)]
+#[component(on_add = insert_checked)]
+struct InitiallyChecked(bool);
+
+fn insert_checked(mut world: DeferredWorld, HookContext { entity, .. }: HookContext) {
+    let checked = world.get::<InitiallyChecked>(entity).unwrap().0;
+    let mut commands = world.commands();
+    let mut entity = commands.entity(entity);
+    entity.remove::<InitiallyChecked>();
+    if checked {
+        entity.insert(Checked);
+    }
+}
+
+/// Shows the check mark of checked checkboxes.
+pub(crate) fn update_checkbox_marks(
+    checkboxes: Query<(Entity, Has<Checked>), With<Checkbox>>,
+    children: Query<&Children>,
+    mut marks: Query<&mut Visibility, With<CheckMark>>,
+) {
+    for (entity, checked) in checkboxes.iter() {
+        let target = if checked {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        for child in children.iter_descendants(entity) {
+            if let Ok(mut mark) = marks.get_mut(child) {
+                mark.set_if_neq(target);
+            }
+        }
+    }
+}
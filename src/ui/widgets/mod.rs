@@ -1,4 +1,6 @@
 mod button;
+mod checkbox;
+mod slider;
 mod window;
 use bevy::{
     ecs::{lifecycle::HookContext, world::DeferredWorld},
@@ -7,6 +9,10 @@ use bevy::{
 };
 pub use button::button;
 pub(crate) use button::button_hover;
+pub use checkbox::checkbox;
+pub(crate) use checkbox::update_checkbox_marks;
+pub use slider::slider;
+pub(crate) use slider::update_slider_thumbs;
 pub use window::window;
 
 #[derive(Component)]
@@ -0,0 +1,82 @@
+use bevy::{
+    feathers::handle_or_path::HandleOrPath,
+    input_focus::tab_navigation::TabIndex,
+    picking::hover::Hovered,
+    prelude::*,
+    ui_widgets::{Slider, SliderRange, SliderStep, SliderThumb, SliderValue, TrackClick},
+};
+
+use crate::ui::{PAPER_THICK_SLICER, widgets::SlicedImage};
+
+const THUMB_WIDTH: f32 = 6.0;
+
+/// Horizontal slider between `min` and `max`. Its value is changed by observing
+/// [`ValueChange<f32>`](bevy::ui_widgets::ValueChange) and updating [`SliderValue`].
+pub fn slider<B: Bundle>(value: f32, min: f32, max: f32, step: f32, overrides: B) -> impl Bundle {
+    (
+        Node {
+            width: px(80),
+            height: px(12),
+            align_items: AlignItems::Center,
+            ..Default::default()
+        },
+        Slider {
+            track_click: TrackClick::Snap,
+        },
+        SliderValue(value),
+        SliderRange::new(min, max),
+        SliderStep(step),
+        Hovered::default(),
+        TabIndex(0),
+        overrides,
+        children![
+            // Track
+            (
+                Node {
+                    width: Val::Percent(100.0),
+                    height: px(4),
+                    ..Default::default()
+                },
+                BackgroundColor(Color::srgb_u8(130, 85, 45)),
+            ),
+            (
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: px(THUMB_WIDTH),
+                    height: Val::Percent(100.0),
+                    margin: UiRect::left(px(-THUMB_WIDTH / 2.0)),
+                    ..Default::default()
+                },
+                SliderThumb,
+                SlicedImage {
+                    image: HandleOrPath::Path("textures/ui/paper_thick.png".to_string()),
+                    slicer: PAPER_THICK_SLICER,
+                },
+            )
+        ],
+    )
+}
+
+/// Moves the thumbs to the value of their slider.
+pub(crate) fn update_slider_thumbs(
+    sliders: Query<
+        (Entity, &SliderValue, &SliderRange),
+        Or<(Changed<SliderValue>, Changed<SliderRange>)>,
+    >,
+    children: Query<&Children>,
+    mut thumbs: Query<&mut Node, With<SliderThumb>>,
+) {
+    for (entity, value, range) in sliders.iter() {
+        let span = range.end() - range.start();
+        let fraction = if span > 0.0 {
+            ((value.0 - range.start()) / span).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        for child in children.iter_descendants(entity) {
+            if let Ok(mut thumb) = thumbs.get_mut(child) {
+                thumb.left = Val::Percent(fraction * 100.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    GameState,
+    day_night::{DayPhase, DayPhaseChanged},
+    enemy::{ENEMY_GROUND_OFFSET, Enemy},
+    world::{CASTLE_SITE, Heightmap},
+};
+
+/// Distance from the castle to the sides of the square on whose border enemies appear.
+const SPAWN_DISTANCE: f32 = 100.0;
+
+/// Waves per day. The last entry is reused for every later day and grows by
+/// `extra_per_day` enemies each day.
+pub const WAVES: &[WaveDefinition] = &[
+    WaveDefinition {
+        day: 1,
+        enemy_count: 3,
+        extra_per_day: 0,
+        spawn_interval: Duration::from_millis(1500),
+    },
+    WaveDefinition {
+        day: 2,
+        enemy_count: 5,
+        extra_per_day: 0,
+        spawn_interval: Duration::from_millis(1200),
+    },
+    WaveDefinition {
+        day: 3,
+        enemy_count: 8,
+        extra_per_day: 0,
+        spawn_interval: Duration::from_millis(1000),
+    },
+    WaveDefinition {
+        day: 5,
+        enemy_count: 12,
+        extra_per_day: 3,
+        spawn_interval: Duration::from_millis(800),
+    },
+];
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveDirector>()
+            .add_observer(follow_day_phase)
+            .add_systems(OnEnter(GameState::InGame), reset_wave_director)
+            .add_systems(
+                Update,
+                spawn_wave_enemies.run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveDefinition {
+    /// First day this definition applies to.
+    pub day: u32,
+    pub enemy_count: u32,
+    /// Enemies added for every day past `day`.
+    pub extra_per_day: u32,
+    /// Time between two consecutive spawns of the same wave.
+    pub spawn_interval: Duration,
+}
+
+/// Triggered when the enemies of a wave start spawning.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WaveStarted {
+    pub day: u32,
+    pub enemy_count: u32,
+}
+
+/// Triggered when a new day begins.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DayStarted {
+    pub day: u32,
+}
+
+#[derive(Resource, Debug)]
+pub struct WaveDirector {
+    day: u32,
+    waves: Vec<WaveDefinition>,
+    spawn_timer: Timer,
+    /// Day of the wave that is currently spawning.
+    wave: u32,
+    /// Enemies of the current wave that still have to be spawned.
+    pending: u32,
+    rng: StdRng,
+}
+
+impl Default for WaveDirector {
+    fn default() -> Self {
+        Self::new(WAVES.to_vec())
+    }
+}
+
+impl WaveDirector {
+    pub fn new(mut waves: Vec<WaveDefinition>) -> Self {
+        waves.sort_by_key(|wave| wave.day);
+        Self {
+            day: 1,
+            waves,
+            spawn_timer: Timer::default(),
+            wave: 0,
+            pending: 0,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    /// Continues at `day`, e.g. when loading a save.
+    pub fn set_day(&mut self, day: u32) {
+        self.day = day.max(1);
+    }
+
+    /// Number of enemies the wave of `day` consists of.
+    pub fn enemy_count(&self, day: u32) -> u32 {
+        self.wave_for_day(day).map_or(0, |wave| {
+            wave.enemy_count
+                .saturating_add(wave.extra_per_day.saturating_mul(day - wave.day))
+        })
+    }
+
+    pub fn wave_for_day(&self, day: u32) -> Option<&WaveDefinition> {
+        self.waves.iter().rev().find(|wave| wave.day <= day)
+    }
+
+    /// Queues the wave of the current day. Any enemies of a previous wave
+    /// that were not spawned yet are dropped.
+    pub fn start_wave(&mut self) -> Option<WaveStarted> {
+        let wave = *self.wave_for_day(self.day)?;
+        self.wave = self.day;
+        self.pending = self.enemy_count(self.day);
+        self.spawn_timer = Timer::new(wave.spawn_interval, TimerMode::Repeating);
+        // Spawn the first enemy right away
+        self.spawn_timer.set_elapsed(wave.spawn_interval);
+        Some(WaveStarted {
+            day: self.day,
+            enemy_count: self.pending,
+        })
+    }
+
+    /// Random point on the border of a square around the castle.
+    fn spawn_point(&mut self) -> Vec2 {
+        let along = self.rng.random_range(-SPAWN_DISTANCE..SPAWN_DISTANCE);
+        let (x, z) = match self.rng.random_range(0..4) {
+            0 => (along, -SPAWN_DISTANCE),
+            1 => (along, SPAWN_DISTANCE),
+            2 => (-SPAWN_DISTANCE, along),
+            _ => (SPAWN_DISTANCE, along),
+        };
+        CASTLE_SITE + Vec2::new(x, z)
+    }
+}
+
+fn reset_wave_director(mut director: ResMut<WaveDirector>) {
+    let waves = std::mem::take(&mut director.waves);
+    *director = WaveDirector::new(waves);
+}
+
+/// The wave of a day attacks at dusk, the next day begins at dawn.
+fn follow_day_phase(
+    phase_changed: On<DayPhaseChanged>,
+    mut commands: Commands,
+    mut director: ResMut<WaveDirector>,
+) {
+    match phase_changed.phase {
+        DayPhase::Dusk => {
+            if let Some(wave_started) = director.start_wave() {
+                commands.trigger(wave_started);
+            }
+        }
+        DayPhase::Dawn => {
+            director.day += 1;
+            commands.trigger(DayStarted { day: director.day });
+        }
+        DayPhase::Day | DayPhase::Night => {}
+    }
+}
+
+fn spawn_wave_enemies(
+    mut commands: Commands,
+    mut director: ResMut<WaveDirector>,
+    heightmap: Option<Res<Heightmap>>,
+    time: Res<Time>,
+) {
+    if director.pending == 0 {
+        return;
+    }
+    director.spawn_timer.tick(time.delta());
+    let count = director
+        .spawn_timer
+        .times_finished_this_tick()
+        .min(director.pending);
+    for _ in 0..count {
+        let point = director.spawn_point();
+        let ground = heightmap
+            .as_ref()
+            .map_or(0.0, |heightmap| heightmap.height_at(point.x, point.y));
+        let position = Vec3::new(point.x, ground + ENEMY_GROUND_OFFSET, point.y);
+        let wave = director.wave;
+        commands.spawn((
+            Name::new("Enemy"),
+            Enemy { wave },
+            Transform::from_translation(position)
+                .looking_at(Vec3::new(CASTLE_SITE.x, position.y, CASTLE_SITE.y), Vec3::Y),
+            DespawnOnExit(GameState::InGame),
+        ));
+    }
+    director.pending -= count;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{advance, headless_app};
+
+    fn wave_app() -> App {
+        let mut app = headless_app();
+        app.insert_state(GameState::InGame).add_plugins(WavePlugin);
+        app.update();
+        app
+    }
+
+    fn change_phase(app: &mut App, previous: DayPhase, phase: DayPhase) {
+        app.world_mut().trigger(DayPhaseChanged { phase, previous });
+    }
+
+    fn enemies_of_wave(app: &mut App, wave: u32) -> usize {
+        let mut enemies = app.world_mut().query::<&Enemy>();
+        enemies
+            .iter(app.world())
+            .filter(|enemy| enemy.wave == wave)
+            .count()
+    }
+
+    #[test]
+    fn waves_spawn_their_enemies_over_time() {
+        let mut app = wave_app();
+
+        change_phase(&mut app, DayPhase::Day, DayPhase::Dusk);
+        app.update();
+        assert_eq!(enemies_of_wave(&mut app, 1), 1);
+
+        advance(&mut app, Duration::from_secs(10));
+        assert_eq!(enemies_of_wave(&mut app, 1), 3);
+
+        change_phase(&mut app, DayPhase::Night, DayPhase::Dawn);
+        change_phase(&mut app, DayPhase::Day, DayPhase::Dusk);
+        advance(&mut app, Duration::from_secs(10));
+        assert_eq!(enemies_of_wave(&mut app, 1), 3);
+        assert_eq!(enemies_of_wave(&mut app, 2), 5);
+    }
+
+    #[test]
+    fn later_days_reuse_the_last_wave_with_extra_enemies() {
+        let mut app = wave_app();
+        app.world_mut().resource_mut::<WaveDirector>().set_day(7);
+
+        change_phase(&mut app, DayPhase::Day, DayPhase::Dusk);
+        advance(&mut app, Duration::from_secs(30));
+        assert_eq!(enemies_of_wave(&mut app, 7), 18);
+    }
+}
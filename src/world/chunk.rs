@@ -0,0 +1,291 @@
+use std::{collections::HashMap, time::Duration};
+
+use avian3d::prelude::{ColliderConstructor, ColliderConstructorHierarchy, RigidBody};
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    game_resources::ResourceKind,
+    harvestable::{Depleted, Harvestable, HarvestableId, restore_depleted},
+    health::Health,
+    player::PLAYER_DAMAGE,
+    settings::Settings,
+    world::{Heightmap, TERRAIN_SIZE, Terrain},
+};
+
+/// Side length of a square chunk in world units.
+pub const CHUNK_SIZE: f32 = 50.0;
+
+/// Chunks along each side of the terrain, half of them on either side of the origin.
+const CHUNKS_PER_SIDE: i32 = (TERRAIN_SIZE / CHUNK_SIZE) as i32;
+
+/// Extra distance before a loaded chunk unloads again, so chunks on the edge of the view
+/// distance don't load and unload over and over.
+const UNLOAD_MARGIN: f32 = CHUNK_SIZE / 2.0;
+
+/// Loads the [`Chunk`]s around every [`ChunkAnchor`] and unloads them once nothing is near.
+pub struct ChunkPlugin;
+
+impl Plugin for ChunkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            stream_chunks.run_if(in_state(GameState::InGame).and(resource_exists::<ChunkMap>)),
+        );
+    }
+}
+
+/// Square part of the world, the chunk at `(0, 0)` extends from the origin towards +X and +Z.
+/// A loaded chunk is an entity holding its terrain and props as children.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Chunk(pub IVec2);
+
+impl Chunk {
+    pub fn containing(position: Vec2) -> Self {
+        Self((position / CHUNK_SIZE).floor().as_ivec2())
+    }
+
+    /// Every chunk of the terrain.
+    pub fn all() -> impl Iterator<Item = Chunk> {
+        let range = -CHUNKS_PER_SIDE / 2..CHUNKS_PER_SIDE / 2;
+        range
+            .clone()
+            .flat_map(move |z| range.clone().map(move |x| Chunk(IVec2::new(x, z))))
+    }
+
+    pub fn area(self) -> Rect {
+        Rect::from_corners(
+            self.0.as_vec2() * CHUNK_SIZE,
+            (self.0 + 1).as_vec2() * CHUNK_SIZE,
+        )
+    }
+
+    /// Distance from `position` to the closest point of the chunk, zero inside of it.
+    pub fn distance(self, position: Vec2) -> f32 {
+        let area = self.area();
+        position.clamp(area.min, area.max).distance(position)
+    }
+}
+
+/// Keeps the chunks around it loaded, e.g. for the player or enemies.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ChunkAnchor {
+    /// Within the view distance of the [`Settings`].
+    #[default]
+    ViewDistance,
+    Radius(f32),
+}
+
+/// Kind of a generated object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropKind {
+    Tree,
+    Rock,
+}
+
+impl PropKind {
+    pub fn max_health(self) -> f32 {
+        match self {
+            // Felled with 12 hits
+            PropKind::Tree => 12.0 * PLAYER_DAMAGE,
+            PropKind::Rock => 8.0 * PLAYER_DAMAGE,
+        }
+    }
+}
+
+/// A generated resource node, spawned whenever its chunk is loaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prop {
+    pub kind: PropKind,
+    pub id: HarvestableId,
+    pub transform: Transform,
+}
+
+/// Props of every chunk, and which chunks are loaded.
+#[derive(Resource, Debug, Default)]
+pub struct ChunkMap {
+    props: HashMap<Chunk, Vec<Prop>>,
+    loaded: HashMap<Chunk, Entity>,
+}
+
+impl ChunkMap {
+    pub fn new(props: impl IntoIterator<Item = Prop>) -> Self {
+        let mut chunk_props = HashMap::<Chunk, Vec<Prop>>::new();
+        for prop in props {
+            let chunk = Chunk::containing(prop.transform.translation.xz());
+            chunk_props.entry(chunk).or_default().push(prop);
+        }
+        Self {
+            props: chunk_props,
+            loaded: HashMap::new(),
+        }
+    }
+}
+
+/// State of a harvested node whose chunk is unloaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeChange {
+    pub health: f32,
+    /// Elapsed [`Time<Virtual>`] at which a depleted node respawns, so it keeps counting down
+    /// while unloaded.
+    pub respawn_at: Option<Duration>,
+}
+
+/// Harvested nodes of unloaded chunks, restored when their chunk loads again.
+#[derive(Resource, Debug, Default)]
+pub struct NodeChanges(HashMap<HarvestableId, NodeChange>);
+
+impl NodeChanges {
+    pub fn insert(&mut self, id: HarvestableId, change: NodeChange) {
+        self.0.insert(id, change);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (HarvestableId, NodeChange)> + '_ {
+        self.0.iter().map(|(id, change)| (*id, *change))
+    }
+}
+
+/// Shared assets of the chunks, created with the world.
+#[derive(Resource, Debug)]
+pub struct ChunkAssets {
+    pub terrain_material: Handle<StandardMaterial>,
+    pub tree: Handle<Scene>,
+    pub rock_mesh: Handle<Mesh>,
+    pub rock_material: Handle<StandardMaterial>,
+}
+
+pub(crate) fn stream_chunks(
+    mut commands: Commands,
+    mut chunk_map: ResMut<ChunkMap>,
+    mut node_changes: ResMut<NodeChanges>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    anchors: Query<(&GlobalTransform, &ChunkAnchor)>,
+    children: Query<&Children>,
+    nodes: Query<(&HarvestableId, &Health, Option<&Depleted>)>,
+    heightmap: Res<Heightmap>,
+    assets: Res<ChunkAssets>,
+    settings: Res<Settings>,
+    time: Res<Time<Virtual>>,
+) {
+    let within = |chunk: Chunk, margin: f32| {
+        anchors.iter().any(|(transform, anchor)| {
+            let radius = match anchor {
+                ChunkAnchor::ViewDistance => settings.view_distance,
+                ChunkAnchor::Radius(radius) => *radius,
+            };
+            chunk.distance(transform.translation().xz()) <= radius + margin
+        })
+    };
+
+    for chunk in Chunk::all() {
+        match chunk_map.loaded.get(&chunk).copied() {
+            Some(entity) if !within(chunk, UNLOAD_MARGIN) => {
+                for (id, health, depleted) in nodes.iter_many(children.iter_descendants(entity)) {
+                    if health.current < health.max || depleted.is_some() {
+                        node_changes.insert(
+                            *id,
+                            NodeChange {
+                                health: health.current,
+                                respawn_at: depleted
+                                    .map(|depleted| time.elapsed() + depleted.remaining()),
+                            },
+                        );
+                    }
+                }
+                commands.entity(entity).despawn();
+                chunk_map.loaded.remove(&chunk);
+            }
+            None if within(chunk, 0.0) => {
+                let props = chunk_map.props.get(&chunk).map_or(&[][..], Vec::as_slice);
+                let entity = spawn_chunk(
+                    &mut commands,
+                    chunk,
+                    props,
+                    &mut node_changes,
+                    &mut meshes,
+                    &heightmap,
+                    &assets,
+                    time.elapsed(),
+                );
+                chunk_map.loaded.insert(chunk, entity);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Spawns the terrain and props of `chunk`, restoring the props' [`NodeChanges`].
+fn spawn_chunk(
+    commands: &mut Commands,
+    chunk: Chunk,
+    props: &[Prop],
+    node_changes: &mut NodeChanges,
+    meshes: &mut Assets<Mesh>,
+    heightmap: &Heightmap,
+    assets: &ChunkAssets,
+    now: Duration,
+) -> Entity {
+    let area = chunk.area();
+    let root = commands
+        .spawn((
+            Name::new(format!("Chunk {}", chunk.0)),
+            chunk,
+            Transform::default(),
+            Visibility::default(),
+            DespawnOnExit(GameState::InGame),
+        ))
+        .id();
+
+    commands.spawn((
+        Name::new("Terrain"),
+        Terrain,
+        Mesh3d(meshes.add(heightmap.mesh(area))),
+        MeshMaterial3d(assets.terrain_material.clone()),
+        heightmap.collider(area),
+        RigidBody::Static,
+        // The heightfield is centered on its entity
+        Transform::from_xyz(area.center().x, 0.0, area.center().y),
+        ChildOf(root),
+    ));
+
+    for prop in props {
+        let change = node_changes
+            .0
+            .remove(&prop.id)
+            // Respawned while its chunk was unloaded
+            .filter(|change| change.respawn_at.is_none_or(|respawn_at| respawn_at > now));
+        let max_health = prop.kind.max_health();
+        let health = Health {
+            current: change.map_or(max_health, |change| change.health),
+            max: max_health,
+        };
+
+        let node = match prop.kind {
+            PropKind::Tree => commands.spawn((
+                SceneRoot(assets.tree.clone()),
+                ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+                Harvestable::new(ResourceKind::Wood, 1),
+            )),
+            PropKind::Rock => commands.spawn((
+                Name::new("Rock"),
+                Mesh3d(assets.rock_mesh.clone()),
+                MeshMaterial3d(assets.rock_material.clone()),
+                ColliderConstructor::ConvexHullFromMesh,
+                Harvestable::new(ResourceKind::Stone, 1),
+            )),
+        }
+        .insert((
+            RigidBody::Static,
+            prop.transform,
+            prop.id,
+            health,
+            ChildOf(root),
+        ))
+        .id();
+
+        if let Some(respawn_at) = change.and_then(|change| change.respawn_at) {
+            restore_depleted(commands, node, respawn_at - now);
+        }
+    }
+    root
+}
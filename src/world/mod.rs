@@ -0,0 +1,234 @@
+use bevy::{
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    prelude::*,
+};
+use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
+
+use crate::{
+    GameState,
+    day_night::Sun,
+    harvestable::HarvestableId,
+    leaf_material::LeafAssets,
+    player::PLAYER_SPAWN,
+    settings::Settings,
+    world::{
+        chunk::{ChunkAssets, ChunkMap, ChunkPlugin, NodeChanges, Prop, PropKind},
+        scatter::{DensityMask, Exclusion, ScatterRule, ScatterScale},
+        terrain::TerrainGenerator,
+    },
+};
+
+pub mod chunk;
+pub mod scatter;
+mod seed;
+pub mod terrain;
+
+pub use seed::WorldSeed;
+pub use terrain::{Heightmap, Terrain};
+
+/// Side length of the square terrain, which is split into chunks.
+pub const TERRAIN_SIZE: f32 = 400.0;
+
+/// Center of the castle, the target of every invader.
+pub const CASTLE_SITE: Vec2 = Vec2::ZERO;
+
+/// Most trees a world can have, their [`HarvestableId`]s start at zero.
+pub const TREE_COUNT: u32 = 200;
+
+/// Most rock deposits a world can have, numbered after the trees.
+pub const ROCK_COUNT: u32 = 80;
+
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldSeed>();
+        app.add_plugins((
+            EguiPlugin::default(),
+            WorldInspectorPlugin::default(),
+            ChunkPlugin,
+        ));
+        app.add_systems(OnEnter(GameState::InGame), setup);
+        app.add_systems(Update, leafs);
+    }
+}
+
+/// Fog in which objects stay visible up to `distance` world units (>= 5% contrast).
+pub fn fog_falloff(distance: f32) -> FogFalloff {
+    FogFalloff::from_visibility_colors(
+        distance,
+        Color::srgb(0.35, 0.5, 0.66), // atmospheric extinction color (after light is lost due to absorption by atmospheric particles)
+        Color::srgb(0.8, 0.844, 1.0), // atmospheric inscattering color (light gained due to scattering from the sun)
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    seed: Res<WorldSeed>,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    // camera
+    commands.spawn((
+        Transform::from_xyz(-1.0, 0.1, 1.0).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+        DistanceFog {
+            color: Color::srgba(0.35, 0.48, 0.66, 1.0),
+            directional_light_color: Color::srgba(1.0, 0.95, 0.85, 0.5),
+            directional_light_exponent: 30.0,
+            falloff: fog_falloff(settings.fog_distance),
+        },
+        DespawnOnExit(GameState::InGame),
+    ));
+
+    // Sun
+    commands.spawn((
+        Sun,
+        DirectionalLight {
+            color: Color::srgb(0.98, 0.95, 0.82),
+            shadows_enabled: settings.shadows,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 0.0).looking_at(Vec3::new(-0.15, -0.05, 0.25), Vec3::Y),
+        DespawnOnExit(GameState::InGame),
+    ));
+
+    // Terrain
+    let terrain_texture_size = 50.0;
+    let heightmap = TerrainGenerator::new(seed.noise_seed("terrain")).generate();
+    let terrain_material = materials.add(StandardMaterial {
+        base_color_texture: Some(asset_server.load_with_settings(
+            "textures/grass.png",
+            |s: &mut _| {
+                *s = ImageLoaderSettings {
+                    sampler: ImageSampler::Descriptor(ImageSamplerDescriptor {
+                        address_mode_u: ImageAddressMode::MirrorRepeat,
+                        address_mode_v: ImageAddressMode::MirrorRepeat,
+                        ..default()
+                    }),
+                    ..default()
+                }
+            },
+        )),
+        uv_transform: Affine2::from_scale(Vec2::splat(TERRAIN_SIZE) / terrain_texture_size),
+        perceptual_roughness: 1.0,
+        ..default()
+    });
+
+    // Trees and rock deposits, spawned by their chunks
+    let trees = tree_transforms(*seed, &heightmap);
+    let rocks = rock_transforms(*seed, &heightmap, &trees);
+    let props = (0..).zip(trees).map(|(id, transform)| Prop {
+        kind: PropKind::Tree,
+        id: HarvestableId(id),
+        transform,
+    });
+    let rock_props = (TREE_COUNT..).zip(rocks).map(|(id, transform)| Prop {
+        kind: PropKind::Rock,
+        id: HarvestableId(id),
+        transform,
+    });
+    commands.insert_resource(ChunkMap::new(props.chain(rock_props)));
+    commands.insert_resource(NodeChanges::default());
+    commands.insert_resource(ChunkAssets {
+        terrain_material,
+        tree: asset_server.load("tree/tree.gltf#Scene0"),
+        rock_mesh: meshes.add(Sphere::new(1.0).mesh().ico(1).unwrap()),
+        rock_material: materials.add(StandardMaterial {
+            base_color: Srgba::hex("8a8580").unwrap().into(),
+            perceptual_roughness: 1.0,
+            ..default()
+        }),
+    });
+
+    commands.insert_resource(heightmap);
+}
+
+/// Where the trees of a world stand, in the order of their [`HarvestableId`]s. Trees grow in
+/// forests with clearings between them.
+pub fn tree_transforms(seed: WorldSeed, heightmap: &Heightmap) -> Vec<Transform> {
+    ScatterRule {
+        count: TREE_COUNT,
+        spacing: 4.0,
+        exclusions: open_ground(),
+        density: Some(DensityMask {
+            frequency: 0.02,
+            threshold: -0.1,
+            falloff: 0.3,
+        }),
+        scale: ScatterScale::Uniform { min: 0.8, max: 1.2 },
+    }
+    .scatter(&mut seed.stream("trees"), heightmap)
+}
+
+/// Where the rock deposits of a world lie, in the order of their [`HarvestableId`]s. Rocks keep
+/// clear of the `trees`.
+pub fn rock_transforms(
+    seed: WorldSeed,
+    heightmap: &Heightmap,
+    trees: &[Transform],
+) -> Vec<Transform> {
+    let mut exclusions = open_ground();
+    exclusions.extend(
+        trees
+            .iter()
+            .map(|tree| Exclusion::new(tree.translation.xz(), 3.0)),
+    );
+    ScatterRule {
+        count: ROCK_COUNT,
+        spacing: 8.0,
+        exclusions,
+        density: None,
+        scale: ScatterScale::PerAxis {
+            min: Vec3::new(0.8, 0.5, 0.8),
+            max: Vec3::new(1.6, 1.0, 1.6),
+        },
+    }
+    .scatter(&mut seed.stream("rocks"), heightmap)
+}
+
+/// Ground kept free of trees and rocks, around the castle and where the player starts.
+fn open_ground() -> Vec<Exclusion> {
+    vec![
+        Exclusion::new(CASTLE_SITE, 16.0),
+        Exclusion::new(PLAYER_SPAWN.xz(), 4.0),
+    ]
+}
+
+fn leafs(
+    mut commands: Commands,
+    mut query: Query<(&Name, &Children), Added<Name>>,
+    leaf_assets: Res<LeafAssets>,
+) {
+    for (name, children) in query.iter_mut() {
+        if !name.contains("leaf") {
+            continue;
+        }
+
+        for child_entity in children.iter() {
+            commands
+                .entity(child_entity)
+                .insert(MeshMaterial3d(leaf_assets.material.clone()))
+                .remove::<MeshMaterial3d<StandardMaterial>>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_scatter_the_same_world_every_time() {
+        let heightmap = TerrainGenerator::new(WorldSeed(1).noise_seed("terrain")).generate();
+        let trees = tree_transforms(WorldSeed(1), &heightmap);
+        assert!(!trees.is_empty());
+        assert_eq!(trees, tree_transforms(WorldSeed(1), &heightmap));
+        assert_ne!(trees, tree_transforms(WorldSeed(2), &heightmap));
+
+        let rocks = rock_transforms(WorldSeed(1), &heightmap, &trees);
+        assert_eq!(rocks, rock_transforms(WorldSeed(1), &heightmap, &trees));
+    }
+}
@@ -0,0 +1,184 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::{Rng, rngs::StdRng, seq::SliceRandom};
+
+use crate::world::{Heightmap, TERRAIN_SIZE};
+
+/// Candidates tried around each point before it stops spawning new ones.
+const CANDIDATE_ATTEMPTS: usize = 30;
+
+/// How objects of one kind, e.g. trees, are spread over the terrain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScatterRule {
+    /// Upper limit of placed objects, fewer fit if the spacing or exclusions are large.
+    pub count: u32,
+    /// Minimum distance between any two objects.
+    pub spacing: f32,
+    /// Areas that stay empty.
+    pub exclusions: Vec<Exclusion>,
+    /// Thins out the objects in patches, placing them evenly if there is none.
+    pub density: Option<DensityMask>,
+    pub scale: ScatterScale,
+}
+
+/// A circle on the ground in which nothing is scattered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exclusion {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Exclusion {
+    pub fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.distance_squared(self.center) < self.radius * self.radius
+    }
+}
+
+/// Noise that decides how likely an object is kept at each point, giving e.g. forests and
+/// clearings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DensityMask {
+    /// Frequency of the patches in cycles per world unit.
+    pub frequency: f64,
+    /// Noise value from -1 to 1 below which nothing is placed.
+    pub threshold: f32,
+    /// Noise range above the threshold over which the density rises to full.
+    pub falloff: f32,
+}
+
+impl DensityMask {
+    fn density(&self, noise: &Fbm<Perlin>, point: Vec2) -> f32 {
+        let value = noise.get([point.x as f64, point.y as f64]) as f32;
+        ((value - self.threshold) / self.falloff).clamp(0.0, 1.0)
+    }
+}
+
+/// Random scale of the scattered objects.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScatterScale {
+    /// The same factor on every axis, keeping the proportions of the model.
+    Uniform {
+        min: f32,
+        max: f32,
+    },
+    PerAxis {
+        min: Vec3,
+        max: Vec3,
+    },
+}
+
+impl ScatterScale {
+    fn sample(&self, rng: &mut StdRng) -> Vec3 {
+        match *self {
+            ScatterScale::Uniform { min, max } => Vec3::splat(rng.random_range(min..=max)),
+            ScatterScale::PerAxis { min, max } => Vec3::new(
+                rng.random_range(min.x..=max.x),
+                rng.random_range(min.y..=max.y),
+                rng.random_range(min.z..=max.z),
+            ),
+        }
+    }
+}
+
+impl ScatterRule {
+    /// Transforms on the ground following this rule, each with a random heading and scale.
+    pub fn scatter(&self, rng: &mut StdRng, heightmap: &Heightmap) -> Vec<Transform> {
+        let density = self.density.map(|mask| {
+            (
+                mask,
+                Fbm::<Perlin>::new(rng.random()).set_frequency(mask.frequency),
+            )
+        });
+
+        let mut points: Vec<Vec2> = poisson_disk(rng, TERRAIN_SIZE, self.spacing)
+            .into_iter()
+            .filter(|point| !self.exclusions.iter().any(|zone| zone.contains(*point)))
+            .filter(|point| {
+                density
+                    .as_ref()
+                    .is_none_or(|(mask, noise)| rng.random::<f32>() < mask.density(noise, *point))
+            })
+            .collect();
+        // Points are sampled outwards from the first, so the kept ones are picked at random
+        points.shuffle(rng);
+        points.truncate(self.count as usize);
+
+        points
+            .into_iter()
+            .map(|point| {
+                Transform::from_xyz(point.x, heightmap.height_at(point.x, point.y), point.y)
+                    .with_scale(self.scale.sample(rng))
+                    .with_rotation(Quat::from_rotation_y(rng.random_range(0.0..TAU)))
+            })
+            .collect()
+    }
+}
+
+/// Points in a square of `size` centered on the origin, no two closer than `spacing` and with no
+/// gaps larger than twice that. Uses Bridson's algorithm.
+pub fn poisson_disk(rng: &mut StdRng, size: f32, spacing: f32) -> Vec<Vec2> {
+    let half = size / 2.0;
+    // Every cell fits at most one point
+    let cell_size = spacing / std::f32::consts::SQRT_2;
+    let cells = (size / cell_size).ceil() as usize;
+    let cell_of = |point: Vec2| {
+        let cell = ((point + half) / cell_size).floor();
+        (
+            (cell.x as usize).min(cells - 1),
+            (cell.y as usize).min(cells - 1),
+        )
+    };
+    let mut grid: Vec<Option<usize>> = vec![None; cells * cells];
+
+    let first = Vec2::new(rng.random_range(-half..half), rng.random_range(-half..half));
+    let mut points = vec![first];
+    let (x, z) = cell_of(first);
+    grid[z * cells + x] = Some(0);
+    let mut active = vec![0];
+
+    while !active.is_empty() {
+        let index = rng.random_range(0..active.len());
+        let center = points[active[index]];
+        let candidate = (0..CANDIDATE_ATTEMPTS)
+            .map(|_| {
+                let distance = rng.random_range(spacing..spacing * 2.0);
+                center + Vec2::from_angle(rng.random_range(0.0..TAU)) * distance
+            })
+            .find(|candidate| {
+                if candidate.x < -half || candidate.x >= half {
+                    return false;
+                }
+                if candidate.y < -half || candidate.y >= half {
+                    return false;
+                }
+                // Only the surrounding cells can hold points within the spacing
+                let (x, z) = cell_of(*candidate);
+                (z.saturating_sub(2)..(z + 3).min(cells)).all(|z| {
+                    (x.saturating_sub(2)..(x + 3).min(cells)).all(|x| {
+                        grid[z * cells + x].is_none_or(|neighbor| {
+                            points[neighbor].distance_squared(*candidate) >= spacing * spacing
+                        })
+                    })
+                })
+            });
+
+        match candidate {
+            Some(candidate) => {
+                let (x, z) = cell_of(candidate);
+                grid[z * cells + x] = Some(points.len());
+                active.push(points.len());
+                points.push(candidate);
+            }
+            None => {
+                active.swap_remove(index);
+            }
+        }
+    }
+    points
+}
@@ -0,0 +1,51 @@
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Seed the world is generated from, the same seed always gives the same world.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// Parses a seed typed by the player. Numbers are used as they are, any other text is hashed
+    /// so words can be shared as seeds too.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.trim();
+        Self(text.parse().unwrap_or_else(|_| hash(text)))
+    }
+
+    /// Independent random stream for a single generation step, e.g. `"trees"`. Steps don't
+    /// share a generator, so changing one leaves the results of the others untouched.
+    pub fn stream(self, step: &str) -> StdRng {
+        StdRng::seed_from_u64(self.0 ^ hash(step))
+    }
+
+    /// Seed for noise functions of a generation step.
+    pub fn noise_seed(self, step: &str) -> u32 {
+        self.stream(step).random()
+    }
+}
+
+impl Default for WorldSeed {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl Display for WorldSeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// FNV-1a, which unlike the std hashers is guaranteed to stay the same across Rust versions.
+fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
@@ -0,0 +1,172 @@
+use avian3d::prelude::Collider;
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+
+use crate::world::{CASTLE_SITE, TERRAIN_SIZE};
+
+/// Vertices along each side of the terrain.
+pub const TERRAIN_RESOLUTION: usize = 257;
+
+/// Marker for the ground, which doesn't count as an obstacle.
+#[derive(Component, Debug, Default)]
+pub struct Terrain;
+
+/// Generates hilly terrain from layered Perlin noise, flattened around the castle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainGenerator {
+    pub seed: u32,
+    pub size: f32,
+    pub resolution: usize,
+    /// Number of noise layers, each adding finer detail.
+    pub octaves: usize,
+    /// Frequency of the coarsest layer in cycles per world unit.
+    pub frequency: f64,
+    /// Highest possible elevation above and below zero.
+    pub amplitude: f32,
+    /// Center of the plateau, which is flat at zero height.
+    pub plateau_center: Vec2,
+    pub plateau_radius: f32,
+    /// Distance over which the plateau blends into the hills.
+    pub plateau_falloff: f32,
+}
+
+impl TerrainGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            size: TERRAIN_SIZE,
+            resolution: TERRAIN_RESOLUTION,
+            octaves: 4,
+            frequency: 0.012,
+            amplitude: 6.0,
+            plateau_center: CASTLE_SITE,
+            plateau_radius: 12.0,
+            plateau_falloff: 16.0,
+        }
+    }
+
+    pub fn generate(&self) -> Heightmap {
+        let noise = Fbm::<Perlin>::new(self.seed)
+            .set_octaves(self.octaves)
+            .set_frequency(self.frequency);
+        let step = self.size / (self.resolution - 1) as f32;
+        let half = self.size / 2.0;
+
+        let mut heights = Vec::with_capacity(self.resolution * self.resolution);
+        for z in 0..self.resolution {
+            for x in 0..self.resolution {
+                let position = Vec2::new(x as f32 * step - half, z as f32 * step - half);
+                let hills = noise.get([position.x as f64, position.y as f64]) as f32;
+                let distance = position.distance(self.plateau_center) - self.plateau_radius;
+                let blend = (distance / self.plateau_falloff).clamp(0.0, 1.0);
+                // Smoothstep, so the plateau has no visible edge
+                let blend = blend * blend * (3.0 - 2.0 * blend);
+                heights.push(hills * self.amplitude * blend);
+            }
+        }
+
+        Heightmap {
+            size: self.size,
+            resolution: self.resolution,
+            heights,
+        }
+    }
+}
+
+/// Elevation of the terrain on a square grid centered on the world origin.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    size: f32,
+    resolution: usize,
+    /// Rows along Z of vertices along X.
+    heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Height of the surface at a world position, interpolated between the vertices.
+    /// Positions outside the terrain get the height of its closest edge.
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let last = (self.resolution - 1) as f32;
+        let to_grid = |position: f32| ((position / self.size + 0.5) * last).clamp(0.0, last);
+        let (grid_x, grid_z) = (to_grid(x), to_grid(z));
+        let (x0, z0) = (grid_x.floor() as usize, grid_z.floor() as usize);
+        let (x1, z1) = (
+            (x0 + 1).min(self.resolution - 1),
+            (z0 + 1).min(self.resolution - 1),
+        );
+        let (tx, tz) = (grid_x.fract(), grid_z.fract());
+
+        let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+        let near = lerp(self.vertex(x0, z0), self.vertex(x1, z0), tx);
+        let far = lerp(self.vertex(x0, z1), self.vertex(x1, z1), tx);
+        lerp(near, far, tz)
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.resolution + x]
+    }
+
+    /// Vertex index closest to a world coordinate, along either axis.
+    fn vertex_index(&self, position: f32) -> usize {
+        let last = (self.resolution - 1) as f32;
+        ((position / self.size + 0.5) * last)
+            .round()
+            .clamp(0.0, last) as usize
+    }
+
+    /// Subdivided mesh of the surface within `area`, relative to its center. UVs span the whole
+    /// terrain once, so the meshes of neighboring areas line up.
+    pub fn mesh(&self, area: Rect) -> Mesh {
+        let last = (self.resolution - 1) as f32;
+        let half = self.size / 2.0;
+        let (min_x, max_x) = (self.vertex_index(area.min.x), self.vertex_index(area.max.x));
+        let (min_z, max_z) = (self.vertex_index(area.min.y), self.vertex_index(area.max.y));
+        let columns = max_x - min_x + 1;
+
+        let mut positions = Vec::new();
+        let mut uvs = Vec::new();
+        for z in min_z..=max_z {
+            for x in min_x..=max_x {
+                let uv = Vec2::new(x as f32, z as f32) / last;
+                let position = uv * self.size - half - area.center();
+                positions.push([position.x, self.vertex(x, z), position.y]);
+                uvs.push(uv.to_array());
+            }
+        }
+
+        let mut indices = Vec::new();
+        for z in 0..(max_z - min_z) as u32 {
+            for x in 0..columns as u32 - 1 {
+                let corner = z * columns as u32 + x;
+                let next_row = corner + columns as u32;
+                // Counter-clockwise seen from above
+                indices.extend([corner, next_row, corner + 1]);
+                indices.extend([corner + 1, next_row, next_row + 1]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+        .with_computed_smooth_normals()
+    }
+
+    /// Heightfield collider matching [`Heightmap::mesh`] of the same `area`.
+    pub fn collider(&self, area: Rect) -> Collider {
+        let (min_x, max_x) = (self.vertex_index(area.min.x), self.vertex_index(area.max.x));
+        let (min_z, max_z) = (self.vertex_index(area.min.y), self.vertex_index(area.max.y));
+        // The outer vector runs along X, the inner one along Z
+        let heights = (min_x..=max_x)
+            .map(|x| (min_z..=max_z).map(|z| self.vertex(x, z)).collect())
+            .collect();
+        Collider::heightfield(heights, Vec3::new(area.width(), 1.0, area.height()))
+    }
+}